@@ -1,66 +1,436 @@
-use rltk::{FontCharType, Point, RGB};
+use rltk::{DiceType, FontCharType, Point, RandomNumberGenerator, RGB};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use specs::error::NoError;
 use specs::prelude::*;
+use specs::saveload::{ConvertSaveload, Marker};
 use specs_derive::*;
 
-#[derive(Component)]
+use crate::map::Map;
+
+#[derive(Component, Copy, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Renderable {
     pub glyph: FontCharType,
     pub foreground: RGB,
     pub background: RGB,
+    // Lower orders are drawn last, so they end up on top of anything sharing the tile
+    pub render_order: i32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Player {}
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Viewshed {
     pub visible_tiles: Vec<Point>,
     pub range: i32,
     pub dirty: bool,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Monster {}
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Name {
     pub name: String,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct BlocksTile {}
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct CombatStats {
     pub max_hp: i32,
     pub hp: i32,
     pub defense: i32,
+    // Added to every melee damage roll
     pub power: i32,
+    // Makes melee attacks against this entity harder to land
+    pub evasion: i32,
+    pub damage: DiceType,
+    // The kind of damage dealt in melee without a weapon
+    pub damage_type: DamageType,
 }
 
-#[derive(Component, Debug, Clone)]
+// Energy builds up by `speed` every tick and the entity acts whenever it has enough,
+// see `initiative_system`
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Initiative {
+    pub speed: i32,
+    pub energy: i32,
+}
+
+// Given to everything the scheduler picked to act on this tick
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MyTurn {}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToMelee {
     pub target: Entity,
 }
 
-#[derive(Component, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum DamageType {
+    Slashing,
+    Piercing,
+    Bludgeoning,
+    Fire,
+    Cold,
+    Poison,
+    Magic,
+}
+
+// One hit waiting to be applied, `source` is whoever dealt it when there was someone
+#[derive(Debug, Clone)]
+pub struct DamageEntry {
+    pub amount: i32,
+    pub source: Option<Entity>,
+    pub kind: DamageType,
+}
+
+// Only ever lives within a turn
+#[derive(Component, Debug, Clone)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>,
+    pub entries: Vec<DamageEntry>,
+}
+
+// The blow that took the last of an entity's hp, kept until the death is dealt with
+#[derive(Component, Debug, Clone)]
+pub struct KilledBy {
+    pub killer: Option<Entity>,
+    pub kind: DamageType,
+}
+
+// What happens where a monster dies, besides it leaving a corpse
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum DeathEffect {
+    // Hurts everything within `radius` that the blast can reach
+    Explode {
+        radius: i32,
+        damage: i32,
+        kind: DamageType,
+    },
+    // Leaves `count` of the monster called `into` in its place
+    Split {
+        into: String,
+        count: i32,
+    },
+}
+
+// One roll on a loot table, `quantity` of `item` are dropped `chance` percent of the time
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct LootDrop {
+    pub item: String,
+    pub chance: i32,
+    #[serde(default = "one")]
+    pub quantity: i32,
+}
+
+fn one() -> i32 {
+    1
+}
+
+// Rolled when the monster dies, every entry separately
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct LootTable {
+    pub drops: Vec<LootDrop>,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct OnDeath {
+    pub effects: Vec<DeathEffect>,
+}
+
+// The remains of a dead monster, drawn under everything else and never in the way
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Corpse {}
+
+// Blocks sight and movement until something bumps into it, see `map::set_door`
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Door {
+    pub open: bool,
+}
+
+// Not drawn until the player spots it
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Hidden {}
+
+// Goes off whenever anything moves onto its tile
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct EntryTrigger {}
+
+// Set on whatever moved this turn, so the TrapSystem knows whose tiles to check. The TrapSystem
+// clears it again in the same run
+#[derive(Component, Debug, Clone)]
+pub struct EntityMoved {}
+
+// Sends whoever triggers it to a random spot on the level
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Teleports {}
+
+// Alerts every monster within `radius` tiles when triggered
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Alarm {
+    pub radius: i32,
+}
+
+// A monster that heard an alarm heads for it, until it gets there or sees the player
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Alerted {
+    pub x: i32,
+    pub y: i32,
+}
+
+// Running totals for everything an entity has hit
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CombatRecord {
+    pub damage_dealt: i32,
+    pub kills: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+}
+
+// How much experience the killing blow earns
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct GivesExperience {
+    pub xp: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Item {}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct InBackpack {
+    pub owner: Entity,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToPickupItem {
+    pub collected_by: Entity,
+    pub item: Entity,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Consumable {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ProvidesHealing {
+    pub heal_amount: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Ranged {
+    pub range: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct InflictsDamage {
+    pub damage: i32,
+    pub kind: DamageType,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct AreaOfEffect {
+    pub radius: i32,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToUseItem {
+    pub item: Entity,
+    pub target: Option<Point>,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToDropItem {
+    pub item: Entity,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+pub enum EquipmentSlot {
+    Melee,
+    Shield,
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Equipped {
+    pub owner: Entity,
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct MeleePowerBonus {
+    pub power: i32,
+}
+
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct DefenseBonus {
+    pub defense: i32,
+}
+
+// A weapon's damage type, used in place of its owner's own
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct MeleeDamageType {
+    pub kind: DamageType,
+}
+
+// Damage of these types is scaled down, on a monster or on the armour its wearer has equipped
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Resistances {
+    pub kinds: Vec<DamageType>,
+}
+
+// Damage of these types is scaled up, the same way as `Resistances`
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Vulnerabilities {
+    pub kinds: Vec<DamageType>,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToRemoveItem {
+    pub item: Entity,
+}
+
+// Shared by every timed effect so they can all be counted down the same way
+pub trait StatusEffect {
+    fn turns(&mut self) -> &mut i32;
+    // How the effect reads in "You are no longer ..."
+    fn description(&self) -> &'static str;
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Confused {
+    pub turns: i32,
+}
+
+impl StatusEffect for Confused {
+    fn turns(&mut self) -> &mut i32 {
+        &mut self.turns
+    }
+
+    fn description(&self) -> &'static str {
+        "confused"
+    }
+}
+
+// `source` is whoever did the poisoning, so a poison kill is credited to them
+#[derive(Component, Debug, Clone)]
+pub struct Poisoned {
+    pub turns: i32,
+    pub damage: i32,
+    pub source: Option<Entity>,
+}
+
+// The derive can't save an `Option<Entity>`, a source that has since gone is saved as none
+#[derive(Serialize, Deserialize)]
+pub struct PoisonedData<M> {
+    turns: i32,
+    damage: i32,
+    source: Option<M>,
+}
+
+impl<M: Marker + Serialize + DeserializeOwned> ConvertSaveload<M> for Poisoned {
+    type Data = PoisonedData<M>;
+    type Error = NoError;
+
+    fn convert_into<F>(&self, mut ids: F) -> Result<Self::Data, Self::Error>
+    where
+        F: FnMut(Entity) -> Option<M>,
+    {
+        Ok(PoisonedData {
+            turns: self.turns,
+            damage: self.damage,
+            source: self.source.and_then(&mut ids),
+        })
+    }
+
+    fn convert_from<F>(data: Self::Data, mut ids: F) -> Result<Self, Self::Error>
+    where
+        F: FnMut(M) -> Option<Entity>,
+    {
+        Ok(Poisoned {
+            turns: data.turns,
+            damage: data.damage,
+            source: data.source.and_then(&mut ids),
+        })
+    }
+}
+
+impl StatusEffect for Poisoned {
+    fn turns(&mut self) -> &mut i32 {
+        &mut self.turns
+    }
+
+    fn description(&self) -> &'static str {
+        "poisoned"
+    }
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Regenerating {
+    pub turns: i32,
+    pub amount: i32,
+}
+
+impl StatusEffect for Regenerating {
+    fn turns(&mut self) -> &mut i32 {
+        &mut self.turns
+    }
+
+    fn description(&self) -> &'static str {
+        "regenerating"
+    }
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct CausesConfusion {
+    pub turns: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct CausesPoison {
+    pub turns: i32,
+    pub damage: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ProvidesRegeneration {
+    pub turns: i32,
+    pub amount: i32,
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(
+        store: &mut WriteStorage<SufferDamage>,
+        victim: Entity,
+        amount: i32,
+        source: Option<Entity>,
+        kind: DamageType,
+    ) {
+        let entry = DamageEntry {
+            amount,
+            source,
+            kind,
+        };
         match store.get_mut(victim) {
-            Some(s) => s.amount.push(amount),
+            Some(s) => s.entries.push(entry),
             None => {
                 let damage = SufferDamage {
-                    amount: vec![amount],
+                    entries: vec![entry],
                 };
                 store
                     .insert(victim, damage)
@@ -69,3 +439,48 @@ impl SufferDamage {
         }
     }
 }
+
+impl Door {
+    pub fn glyph(&self) -> FontCharType {
+        match self.open {
+            true => rltk::to_cp437('/'),
+            false => rltk::to_cp437('+'),
+        }
+    }
+}
+
+impl std::fmt::Display for DamageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            DamageType::Slashing => "slashing",
+            DamageType::Piercing => "piercing",
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+            DamageType::Magic => "magic",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Experience {
+    // Each level takes a little more experience than the last
+    pub fn xp_to_next_level(&self) -> i32 {
+        self.level * 50
+    }
+}
+
+// Marks the entities that get written to the save file
+pub struct SerializeMe;
+
+// Carries the resources that need saving through the save file as a component on a
+// temporary entity, since specs only serializes components
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct SerializationHelper {
+    pub map: Map,
+    pub turn: u32,
+    pub monsters: usize,
+    pub seed: u64,
+    pub rng: RandomNumberGenerator,
+}
@@ -1,39 +1,400 @@
-use crate::components::{CombatStats, SufferDamage};
+use rltk::{Point, RandomNumberGenerator, RGB};
 use specs::prelude::*;
 
+use crate::components::{
+    BlocksTile, CombatRecord, CombatStats, DamageType, DeathEffect, Equipped, Experience,
+    GivesExperience, InBackpack, KilledBy, LootTable, Monster, Name, OnDeath, Position, Renderable,
+    Resistances, SufferDamage, Vulnerabilities,
+};
+use crate::game::RunState;
+use crate::gamelog::GameLog;
+use crate::map::{Map, TileType};
+use crate::raws::RawMaster;
+use crate::spawner;
+
+// What every level up adds to the player's stats
+const LEVEL_UP_HP: i32 = 5;
+const LEVEL_UP_POWER: i32 = 1;
+
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, RawMaster>,
+        ReadStorage<'a, Name>,
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, KilledBy>,
+        WriteStorage<'a, CombatRecord>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Vulnerabilities>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage) = data;
+        let (
+            entities,
+            mut log,
+            raws,
+            names,
+            mut stats,
+            mut damage,
+            mut killed_by,
+            mut records,
+            equipped,
+            resistances,
+            vulnerabilities,
+        ) = data;
+        let rules = &raws.raws.combat;
 
-        for (mut stats, damage) in (&mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
-        }
+        for (victim, stats, damage) in (&entities, &mut stats, &damage).join() {
+            // Equipped armour protects its wearer the same as the wearer's own hide
+            let protections: Vec<Entity> = (&entities, &equipped)
+                .join()
+                .filter(|(_, item)| item.owner == victim)
+                .map(|(item, _)| item)
+                .chain(std::iter::once(victim))
+                .collect();
+
+            for entry in damage.entries.iter() {
+                let resists = protections.iter().any(|entity| {
+                    resistances
+                        .get(*entity)
+                        .is_some_and(|resistances| resistances.kinds.contains(&entry.kind))
+                });
+                let vulnerable = protections.iter().any(|entity| {
+                    vulnerabilities
+                        .get(*entity)
+                        .is_some_and(|vulnerabilities| vulnerabilities.kinds.contains(&entry.kind))
+                });
+                let mut multiplier = 1.0;
+                if resists {
+                    multiplier *= rules.resistance_multiplier;
+                }
+                if vulnerable {
+                    multiplier *= rules.vulnerability_multiplier;
+                }
+                let amount = (entry.amount as f32 * multiplier).round() as i32;
 
+                let name = names
+                    .get(victim)
+                    .map_or("something", |name| name.name.as_str());
+                match (resists, vulnerable) {
+                    (true, false) => log.push(
+                        format!(
+                            "{} resists the {} damage, taking {} hp.",
+                            name, entry.kind, amount
+                        ),
+                        RGB::named(rltk::GREY),
+                    ),
+                    (false, true) => log.push(
+                        format!(
+                            "{} is vulnerable to {}, taking {} hp!",
+                            name, entry.kind, amount
+                        ),
+                        RGB::named(rltk::YELLOW),
+                    ),
+                    _ => {}
+                }
+
+                let was_alive = stats.hp > 0;
+                stats.hp -= amount;
+
+                // Hurting yourself, with a fumble say, doesn't count towards your record
+                let dealt_by = entry.source.filter(|source| *source != victim);
+                if let Some(record) = dealt_by.and_then(|source| records.entry(source).ok()) {
+                    record.or_insert_with(Default::default).damage_dealt += amount;
+                }
+                // Whoever takes the last hp gets the credit, later blows don't change that
+                if was_alive && stats.hp < 1 {
+                    killed_by
+                        .insert(
+                            victim,
+                            KilledBy {
+                                killer: entry.source,
+                                kind: entry.kind,
+                            },
+                        )
+                        .expect("Unable to insert killed by");
+                }
+            }
+        }
         damage.clear();
     }
 }
 
 pub fn delete_the_dead(ecs: &mut World) {
-    let mut dead: Vec<Entity> = Vec::new();
-    // Using a scope to make the borrow checker happy
-    {
-        let combat_stats = ecs.read_storage::<CombatStats>();
-        let entities = ecs.entities();
-        for (entity, stats) in (&entities, &combat_stats).join() {
-            if stats.hp < 1 {
-                dead.push(entity);
+    let player_entity = *ecs.fetch::<Entity>();
+    // A dead player is only dealt with once, it stays around with no hp until the game over screen
+    // has been through
+    let mut handled: Vec<Entity> = Vec::new();
+    if *ecs.fetch::<RunState>() == RunState::GameOver {
+        handled.push(player_entity);
+    }
+
+    // Death effects can kill in turn, so this carries on until nobody new has died
+    loop {
+        let mut dead: Vec<(Entity, Option<KilledBy>)> = Vec::new();
+        // Using a scope to make the borrow checker happy
+        {
+            let combat_stats = ecs.read_storage::<CombatStats>();
+            let killed_by = ecs.read_storage::<KilledBy>();
+            let entities = ecs.entities();
+            for (entity, stats) in (&entities, &combat_stats).join() {
+                if stats.hp < 1 && !handled.contains(&entity) {
+                    dead.push((entity, killed_by.get(entity).cloned()));
+                }
+            }
+        }
+        if dead.is_empty() {
+            break;
+        }
+
+        // Every kill is credited before anyone is deleted, the killer may have died this turn too
+        for (victim, killed_by) in dead.iter() {
+            log_death(ecs, *victim, killed_by.as_ref());
+            let killer = killed_by.as_ref().and_then(|killed_by| killed_by.killer);
+            if let Some(killer) = killer.filter(|killer| killer != victim) {
+                credit_kill(ecs, killer, *victim);
             }
         }
+        for (victim, _) in dead.iter() {
+            if *victim != player_entity {
+                leave_remains(ecs, *victim);
+            }
+        }
+
+        handled.extend(dead.iter().map(|(victim, _)| *victim));
+        DamageSystem {}.run_now(ecs);
     }
 
-    for victim in dead {
+    for victim in handled {
+        // The player entity stays around so the game over screen can still read from it
+        if victim == player_entity {
+            *ecs.write_resource::<RunState>() = RunState::GameOver;
+            continue;
+        }
         ecs.delete_entity(victim).expect("Unable to delete");
     }
 }
+
+// Leaves a corpse where a monster died and sets off its death effects
+fn leave_remains(ecs: &mut World, victim: Entity) {
+    let Some(position) = ecs.read_storage::<Position>().get(victim).copied() else {
+        return;
+    };
+    let name = ecs
+        .read_storage::<Name>()
+        .get(victim)
+        .map_or("Something".to_string(), |name| name.name.clone());
+    let foreground = ecs
+        .read_storage::<Renderable>()
+        .get(victim)
+        .map_or(RGB::named(rltk::WHITE), |render| render.foreground);
+    let effects = ecs
+        .read_storage::<OnDeath>()
+        .get(victim)
+        .map_or(Vec::new(), |on_death| on_death.effects.clone());
+
+    if ecs.read_storage::<Monster>().get(victim).is_some() {
+        spawner::corpse(ecs, &name, position.x, position.y, foreground);
+    }
+
+    let depth = ecs.fetch::<Map>().depth;
+    for effect in effects {
+        match effect {
+            DeathEffect::Explode {
+                radius,
+                damage,
+                kind,
+            } => {
+                ecs.fetch_mut::<GameLog>()
+                    .push(format!("{} explodes!", name), RGB::named(rltk::ORANGE));
+                let blast = {
+                    let map = ecs.fetch::<Map>();
+                    rltk::field_of_view(Point::new(position.x, position.y), radius, &*map)
+                };
+                let entities = ecs.entities();
+                let positions = ecs.read_storage::<Position>();
+                let stats = ecs.read_storage::<CombatStats>();
+                let mut suffer_damage = ecs.write_storage::<SufferDamage>();
+                for (target, target_position, target_stats) in
+                    (&entities, &positions, &stats).join()
+                {
+                    let in_blast = blast
+                        .iter()
+                        .any(|tile| tile.x == target_position.x && tile.y == target_position.y);
+                    if in_blast && target != victim && target_stats.hp > 0 {
+                        SufferDamage::new_damage(
+                            &mut suffer_damage,
+                            target,
+                            damage,
+                            Some(victim),
+                            kind,
+                        );
+                    }
+                }
+            }
+            DeathEffect::Split { into, count } => {
+                ecs.fetch_mut::<GameLog>()
+                    .push(format!("{} splits apart!", name), RGB::named(rltk::ORANGE));
+                for (x, y) in free_tiles_around(ecs, position)
+                    .into_iter()
+                    .take(count as usize)
+                {
+                    spawner::spawn_named(ecs, &into, x, y, depth);
+                }
+            }
+        }
+    }
+
+    drop_loot(ecs, victim, position);
+}
+
+// Everything the victim carried or wore ends up on its tile, along with whatever its loot
+// table rolls
+fn drop_loot(ecs: &mut World, victim: Entity, position: Position) {
+    let carried: Vec<Entity> = {
+        let entities = ecs.entities();
+        let equipped = ecs.read_storage::<Equipped>();
+        let backpack = ecs.read_storage::<InBackpack>();
+        let worn = (&entities, &equipped)
+            .join()
+            .filter(|(_, worn)| worn.owner == victim)
+            .map(|(item, _)| item);
+        let packed = (&entities, &backpack)
+            .join()
+            .filter(|(_, packed)| packed.owner == victim)
+            .map(|(item, _)| item);
+        worn.chain(packed).collect()
+    };
+    for item in carried {
+        ecs.write_storage::<Equipped>().remove(item);
+        ecs.write_storage::<InBackpack>().remove(item);
+        ecs.write_storage::<Position>()
+            .insert(item, position)
+            .expect("Unable to drop item");
+    }
+
+    let drops = ecs
+        .read_storage::<LootTable>()
+        .get(victim)
+        .map_or(Vec::new(), |table| table.drops.clone());
+    let depth = ecs.fetch::<Map>().depth;
+    for drop in drops {
+        let roll = ecs
+            .write_resource::<RandomNumberGenerator>()
+            .roll_dice(1, 100);
+        if roll <= drop.chance {
+            for _ in 0..drop.quantity {
+                spawner::spawn_named(ecs, &drop.item, position.x, position.y, depth);
+            }
+        }
+    }
+}
+
+// The tile at `centre` and the floor around it, nearest first, without anything living in the way
+fn free_tiles_around(ecs: &World, centre: Position) -> Vec<(i32, i32)> {
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let blockers = ecs.read_storage::<BlocksTile>();
+    let stats = ecs.read_storage::<CombatStats>();
+    let occupied: Vec<(i32, i32)> = (&positions, &blockers, &stats)
+        .join()
+        .filter(|(_, _, stats)| stats.hp > 0)
+        .map(|(position, _, _)| (position.x, position.y))
+        .collect();
+
+    let mut tiles = vec![(centre.x, centre.y)];
+    for delta_y in -1..=1 {
+        for delta_x in -1..=1 {
+            if delta_x != 0 || delta_y != 0 {
+                tiles.push((centre.x + delta_x, centre.y + delta_y));
+            }
+        }
+    }
+    tiles.retain(|&(x, y)| {
+        x > 0
+            && x < map.width - 1
+            && y > 0
+            && y < map.height - 1
+            && map.tiles[map.xy_index(x, y)] != TileType::Wall
+            && !occupied.contains(&(x, y))
+    });
+    tiles
+}
+
+// e.g. `Goblin #2 was killed by Orc #3` or `You succumbed to poison`
+fn log_death(ecs: &World, victim: Entity, killed_by: Option<&KilledBy>) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let name_of = |entity: Entity| {
+        names
+            .get(entity)
+            .map_or("something", |name| name.name.as_str())
+            .to_string()
+    };
+
+    let victim_name = match victim == player_entity {
+        true => "You".to_string(),
+        false => name_of(victim),
+    };
+    let killer = killed_by.and_then(|killed_by| killed_by.killer);
+    let kind = killed_by.map(|killed_by| killed_by.kind);
+    let message = match (killer, kind) {
+        (Some(killer), _) if killer == victim && killer == player_entity => {
+            "You killed yourself.".to_string()
+        }
+        (Some(killer), _) if killer == victim => format!("{} killed itself.", victim_name),
+        (Some(killer), _) if killer == player_entity => format!("You killed {}.", victim_name),
+        (Some(killer), _) if victim == player_entity => {
+            format!("You were killed by {}.", name_of(killer))
+        }
+        (Some(killer), _) => format!("{} was killed by {}.", victim_name, name_of(killer)),
+        (None, Some(DamageType::Poison)) => format!("{} succumbed to poison.", victim_name),
+        (None, _) => format!("{} died.", victim_name),
+    };
+    ecs.fetch_mut::<GameLog>()
+        .push(message, RGB::named(rltk::RED));
+}
+
+// Counts the kill on the killer's record and hands over the victim's experience
+fn credit_kill(ecs: &World, killer: Entity, victim: Entity) {
+    let mut records = ecs.write_storage::<CombatRecord>();
+    if let Ok(record) = records.entry(killer) {
+        record.or_insert_with(Default::default).kills += 1;
+    }
+
+    let Some(worth) = ecs.read_storage::<GivesExperience>().get(victim).cloned() else {
+        return;
+    };
+    let mut experience = ecs.write_storage::<Experience>();
+    let mut stats = ecs.write_storage::<CombatStats>();
+    let (Some(experience), Some(stats)) = (experience.get_mut(killer), stats.get_mut(killer))
+    else {
+        return;
+    };
+    // Levelling up heals, which mustn't bring back a killer that died in the same fight
+    if stats.hp < 1 {
+        return;
+    }
+
+    experience.xp += worth.xp;
+    while experience.xp >= experience.xp_to_next_level() {
+        experience.xp -= experience.xp_to_next_level();
+        experience.level += 1;
+        stats.max_hp += LEVEL_UP_HP;
+        stats.hp = stats.max_hp;
+        stats.power += LEVEL_UP_POWER;
+
+        if killer == *ecs.fetch::<Entity>() {
+            ecs.fetch_mut::<GameLog>().push(
+                format!("Welcome to level {}! You feel stronger.", experience.level),
+                RGB::named(rltk::MAGENTA),
+            );
+        }
+    }
+}
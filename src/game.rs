@@ -1,43 +1,105 @@
-use rltk::{GameState, Point, Rltk, RGB};
+use rltk::{BEvent, GameState, Point, RandomNumberGenerator, Rltk, RGB};
 use specs::prelude::*;
+use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
 
 use crate::components::{
-    BlocksTile, CombatStats, Monster, Name, Player, Position, Renderable, SufferDamage, Viewshed,
-    WantsToMelee,
+    Alarm, Alerted, AreaOfEffect, BlocksTile, CausesConfusion, CausesPoison, CombatRecord,
+    CombatStats, Confused, Consumable, Corpse, DefenseBonus, Door, EntityMoved, EntryTrigger,
+    Equippable, Equipped, Experience, GivesExperience, Hidden, InBackpack, InflictsDamage,
+    Initiative, Item, KilledBy, LootTable, MeleeDamageType, MeleePowerBonus, Monster, MyTurn, Name,
+    OnDeath, Player, Poisoned, Position, ProvidesHealing, ProvidesRegeneration, Ranged,
+    Regenerating, Renderable, Resistances, SerializationHelper, SerializeMe, SufferDamage,
+    Teleports, Viewshed, Vulnerabilities, WantsToDropItem, WantsToMelee, WantsToPickupItem,
+    WantsToRemoveItem, WantsToUseItem,
+};
+use crate::damage_system::{delete_the_dead, DamageSystem};
+use crate::gamelog::GameLog;
+use crate::gui::{
+    self, GameOverResult, ItemMenuResult, MainMenuResult, MainMenuSelection, TargetCursor,
+};
+use crate::initiative_system::InitiativeSystem;
+use crate::inventory_system::{
+    ItemCollectionSystem, ItemDropSystem, ItemRemoveSystem, ItemUseSystem,
 };
-use crate::damage_system::DamageSystem;
 use crate::map::{draw_map, Map};
+use crate::map_builders;
 use crate::map_indexing_system::MapIndexingSystem;
 use crate::melee_combat_system::MeleeCombatSystem;
 use crate::monster_ai_system::MonsterAI;
-use crate::player::player_input;
+use crate::options::Options;
+use crate::player::{perform_command, player_input, Command};
+use crate::raws::RawMaster;
+use crate::save_load_system;
+use crate::spawner;
+use crate::status_effect_system::StatusEffectSystem;
+use crate::trap_system::TrapSystem;
 use crate::visibility_system::VisibilitySystem;
 
 pub static PLAYER_VIEWSHED: i32 = 8;
 
 pub struct State {
     pub ecs: World,
+    pub options: Options,
 }
 
-#[derive(PartialEq, Copy, Clone)]
+// Counts the player's actions, starting from 1 on the first turn
+pub struct TurnCounter(pub u32);
+
+// The number given to the last monster spawned, it only goes up so no two monsters in a game
+// share one
+pub struct MonsterCounter(pub usize);
+
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub enum RunState {
     AwaitingInput,
     PreRun,
+    // The one run straight after each player action. Anything that should happen once per
+    // action, however fast everyone else is, only runs in this state
     PlayerTurn,
-    MonsterTurn,
+    Ticking,
+    NextLevel,
+    ShowInventory,
+    ShowDropItem,
+    ShowRemoveItem,
+    ShowTargeting { range: i32, item: Entity },
+    SaveGame,
+    MainMenu { menu_selection: MainMenuSelection },
+    GameOver,
 }
 
 impl State {
     fn run_systems(&mut self) {
+        let mut initiative = InitiativeSystem {};
+        initiative.run_now(&self.ecs);
+
         let mut visibility_system = VisibilitySystem {};
         visibility_system.run_now(&self.ecs);
 
         let mut monster_system = MonsterAI {};
         monster_system.run_now(&self.ecs);
 
+        let mut traps = TrapSystem {};
+        traps.run_now(&self.ecs);
+
+        let mut status_effects = StatusEffectSystem {};
+        status_effects.run_now(&self.ecs);
+
         let mut map_index_system = MapIndexingSystem {};
         map_index_system.run_now(&self.ecs);
 
+        // Items go before combat, so whatever a scroll kills is dead before it can hit back
+        let mut pickup = ItemCollectionSystem {};
+        pickup.run_now(&self.ecs);
+
+        let mut items = ItemUseSystem {};
+        items.run_now(&self.ecs);
+
+        let mut drop_items = ItemDropSystem {};
+        drop_items.run_now(&self.ecs);
+
+        let mut remove_items = ItemRemoveSystem {};
+        remove_items.run_now(&self.ecs);
+
         let mut melee_combat_system = MeleeCombatSystem {};
         melee_combat_system.run_now(&self.ecs);
 
@@ -46,165 +108,474 @@ impl State {
 
         self.ecs.maintain();
     }
-}
 
-impl GameState for State {
-    fn tick(&mut self, context: &mut Rltk) {
-        context.cls();
+    // Builds a map for `depth`, fills it with monsters and drops the player at its start
+    fn generate_world_map(&mut self, depth: i32) {
+        let mut builder;
+        {
+            let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
+            builder = match &self.options.builder {
+                Some(name) => map_builders::builder_by_name(name, depth).unwrap(),
+                None => map_builders::random_builder(&mut rng, depth),
+            };
+            builder.build_map(&mut rng);
+        }
+        self.ecs.insert(builder.get_map());
+        builder.spawn_entities(&mut self.ecs);
 
-        let mut new_run_state;
+        let start = builder.get_starting_position();
+        let mut player_point = self.ecs.write_resource::<Point>();
+        *player_point = Point::new(start.x, start.y);
+
+        let player_entity = self.ecs.fetch::<Entity>();
+        let mut positions = self.ecs.write_storage::<Position>();
+        if let Some(player_position) = positions.get_mut(*player_entity) {
+            *player_position = start;
+        }
+        let mut viewsheds = self.ecs.write_storage::<Viewshed>();
+        if let Some(viewshed) = viewsheds.get_mut(*player_entity) {
+            viewshed.dirty = true;
+        }
+    }
+
+    // Everything on the old level goes, apart from the player and what they carry or wear
+    fn entities_to_remove_on_level_change(&mut self) -> Vec<Entity> {
+        let entities = self.ecs.entities();
+        let player_entity = self.ecs.fetch::<Entity>();
+        let backpack = self.ecs.read_storage::<InBackpack>();
+        let equipped = self.ecs.read_storage::<Equipped>();
+
+        entities
+            .join()
+            .filter(|entity| *entity != *player_entity)
+            .filter(|entity| backpack.get(*entity).map(|item| item.owner) != Some(*player_entity))
+            .filter(|entity| equipped.get(*entity).map(|item| item.owner) != Some(*player_entity))
+            .collect()
+    }
+
+    // Throws away whatever was being played and starts over on the first level with `options.seed`
+    pub fn new_game(&mut self) {
+        self.ecs.delete_all();
+        self.ecs.maintain();
+
+        *self.ecs.write_resource::<RandomNumberGenerator>() =
+            RandomNumberGenerator::seeded(self.options.seed);
+        *self.ecs.write_resource::<TurnCounter>() = TurnCounter(0);
+        *self.ecs.write_resource::<MonsterCounter>() = MonsterCounter(0);
         {
-            let run_state = self.ecs.fetch::<RunState>();
-            new_run_state = *run_state;
+            let mut log = self.ecs.write_resource::<GameLog>();
+            log.clear();
+            log.push("Welcome to the dungeon!", RGB::named(rltk::YELLOW));
         }
 
-        match new_run_state {
-            RunState::AwaitingInput => {
-                new_run_state = player_input(self, context);
+        let player_entity = spawner::player(&mut self.ecs, 0, 0);
+        self.ecs.insert(player_entity);
+        self.generate_world_map(1);
+    }
+
+    // Loads the saved game and picks up its seed, ready for the first turn. A save with bad data,
+    // from another version or otherwise, never will load, so it is deleted. Failing to read it is
+    // left for next time
+    pub fn continue_game(&mut self) -> std::io::Result<()> {
+        if !save_load_system::does_save_exist(&self.options.save_file) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "There is no saved game to continue",
+            ));
+        }
+        match save_load_system::load_game(&mut self.ecs, &self.options.save_file) {
+            Ok(seed) => self.options.seed = seed,
+            Err(error) if error.kind() == std::io::ErrorKind::InvalidData => {
+                save_load_system::delete_save(&self.options.save_file);
+                return Err(std::io::Error::new(
+                    error.kind(),
+                    format!("{}, the save has been deleted", error),
+                ));
             }
+            Err(error) => return Err(error),
+        }
+        self.ecs.insert(RunState::PreRun);
+        Ok(())
+    }
+
+    // Leaves the game over screen for the main menu. The dead player goes with the rest of the
+    // world, it would only die again on the next state change otherwise, and the next game
+    // shouldn't replay the dungeon that killed them
+    pub fn quit_to_menu(&mut self) -> RunState {
+        self.options.seed = RandomNumberGenerator::new().next_u64();
+        self.new_game();
+        RunState::MainMenu {
+            menu_selection: MainMenuSelection::NewGame,
+        }
+    }
+
+    fn save(&mut self) {
+        if let Err(error) =
+            save_load_system::save_game(&mut self.ecs, self.options.seed, &self.options.save_file)
+        {
+            rltk::console::log(format!("Unable to save the game: {}", error));
+        }
+    }
+
+    // Only a game in progress is worth coming back to, a dead player has already lost their save
+    fn save_and_quit(&mut self, context: &mut Rltk) {
+        let run_state = *self.ecs.fetch::<RunState>();
+        if !matches!(run_state, RunState::MainMenu { .. } | RunState::GameOver) {
+            self.save();
+        }
+        context.quit();
+    }
+
+    pub fn run_state(&self) -> RunState {
+        *self.ecs.fetch::<RunState>()
+    }
+
+    // Dead entities are cleared out whenever the state changes, which is also where the
+    // player's death turns into `GameOver`
+    pub fn set_run_state(&mut self, run_state: RunState) {
+        *self.ecs.write_resource::<RunState>() = run_state;
+        delete_the_dead(&mut self.ecs);
+
+        // Permadeath, there's nothing to come back to
+        if self.run_state() == RunState::GameOver && run_state != RunState::GameOver {
+            save_load_system::delete_save(&self.options.save_file);
+        }
+    }
+
+    // Moves the turn logic on from any state that doesn't wait on the player, the rest are
+    // handed back unchanged
+    pub fn advance(&mut self, run_state: RunState) -> RunState {
+        match run_state {
             RunState::PreRun => {
                 self.run_systems();
-                new_run_state = RunState::AwaitingInput;
+                RunState::AwaitingInput
             }
             RunState::PlayerTurn => {
+                self.ecs.fetch_mut::<TurnCounter>().0 += 1;
                 self.run_systems();
-                new_run_state = RunState::MonsterTurn;
+                RunState::Ticking
             }
-            RunState::MonsterTurn => {
+            // One tick of the scheduler, the initiative system hands control back to the player
+            // once it's their turn
+            RunState::Ticking => {
                 self.run_systems();
-                new_run_state = RunState::AwaitingInput;
+                self.run_state()
             }
+            RunState::NextLevel => {
+                self.goto_next_level();
+                RunState::PreRun
+            }
+            _ => run_state,
         }
+    }
 
-        {
-            let mut run_writer = self.ecs.write_resource::<RunState>();
-            *run_writer = new_run_state;
-        }
-
+    fn draw(&self, context: &mut Rltk) {
         draw_map(&self.ecs, context);
 
+        let entities = self.ecs.entities();
         let positions = self.ecs.read_storage::<Position>();
         let renderables = self.ecs.read_storage::<Renderable>();
+        let confused = self.ecs.read_storage::<Confused>();
+        let poisoned = self.ecs.read_storage::<Poisoned>();
+        let regenerating = self.ecs.read_storage::<Regenerating>();
+        let hidden = self.ecs.read_storage::<Hidden>();
         let map = self.ecs.fetch::<Map>();
 
-        for (position, render) in (&positions, &renderables).join() {
+        let mut data = (&entities, &positions, &renderables, !&hidden)
+            .join()
+            .map(|(entity, position, render, _)| (entity, position, render))
+            .collect::<Vec<_>>();
+        data.sort_by_key(|(_, _, render)| -render.render_order);
+        for (entity, position, render) in data {
             let index = map.xy_index(position.x, position.y);
             if map.visible_tiles[index] {
+                // Anything under a status effect is picked out by its background
+                let background = if confused.get(entity).is_some() {
+                    RGB::named(rltk::PURPLE)
+                } else if poisoned.get(entity).is_some() {
+                    RGB::named(rltk::DARK_GREEN)
+                } else if regenerating.get(entity).is_some() {
+                    RGB::named(rltk::NAVY)
+                } else {
+                    render.background
+                };
                 context.set(
                     position.x,
                     position.y,
                     render.foreground,
-                    render.background,
+                    background,
                     render.glyph,
                 )
             }
         }
+
+        gui::draw_ui(&self.ecs, context, self.options.seed);
+    }
+
+    fn goto_next_level(&mut self) {
+        let to_delete = self.entities_to_remove_on_level_change();
+        for target in to_delete {
+            self.ecs
+                .delete_entity(target)
+                .expect("Unable to delete entity");
+        }
+
+        let current_depth = self.ecs.fetch::<Map>().depth;
+        self.generate_world_map(current_depth + 1);
     }
 }
 
-pub fn run() -> rltk::BError {
+impl GameState for State {
+    fn tick(&mut self, context: &mut Rltk) {
+        context.cls();
+
+        let mut close_requested = false;
+        rltk::INPUT.lock().for_each_message(|event| {
+            if event == BEvent::CloseRequested {
+                close_requested = true;
+            }
+        });
+        if close_requested {
+            self.save_and_quit(context);
+            return;
+        }
+
+        let mut new_run_state;
+        {
+            let run_state = self.ecs.fetch::<RunState>();
+            new_run_state = *run_state;
+        }
+
+        // The menus take over the whole screen
+        if !matches!(
+            new_run_state,
+            RunState::MainMenu { .. } | RunState::GameOver
+        ) {
+            self.draw(context);
+        }
+
+        match new_run_state {
+            RunState::AwaitingInput => {
+                new_run_state = player_input(self, context);
+            }
+            RunState::PreRun | RunState::PlayerTurn | RunState::Ticking | RunState::NextLevel => {
+                new_run_state = self.advance(new_run_state);
+            }
+            RunState::ShowInventory => {
+                let (result, item) = gui::show_inventory(self, context);
+                match result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item = item.unwrap();
+                        let range = self
+                            .ecs
+                            .read_storage::<Ranged>()
+                            .get(item)
+                            .map(|ranged| ranged.range);
+                        match range {
+                            Some(range) => {
+                                // The cursor starts on the player and only jumps to the mouse
+                                // once it moves from where it is now
+                                let player_position = *self.ecs.fetch::<Point>();
+                                *self.ecs.fetch_mut::<TargetCursor>() = TargetCursor {
+                                    position: player_position,
+                                    mouse: context.mouse_point(),
+                                };
+                                new_run_state = RunState::ShowTargeting { range, item };
+                            }
+                            None => {
+                                new_run_state = perform_command(
+                                    &mut self.ecs,
+                                    Command::UseItem { item, target: None },
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            RunState::SaveGame => {
+                self.save();
+                // The save keeps its own seed, a new game from the menu gets a dungeon of its own
+                self.options.seed = RandomNumberGenerator::new().next_u64();
+                new_run_state = RunState::MainMenu {
+                    menu_selection: MainMenuSelection::Continue,
+                };
+            }
+            RunState::MainMenu { .. } => match gui::main_menu(self, context) {
+                MainMenuResult::NoSelection { selected } => {
+                    new_run_state = RunState::MainMenu {
+                        menu_selection: selected,
+                    }
+                }
+                MainMenuResult::Selected { selected } => match selected {
+                    MainMenuSelection::NewGame => {
+                        self.new_game();
+                        new_run_state = RunState::PreRun;
+                    }
+                    MainMenuSelection::Continue => match self.continue_game() {
+                        Ok(()) => new_run_state = RunState::PreRun,
+                        Err(error) => {
+                            rltk::console::log(format!("Unable to load the game: {}", error));
+                        }
+                    },
+                    MainMenuSelection::Quit => {
+                        context.quit();
+                    }
+                },
+            },
+            RunState::GameOver => {
+                if gui::game_over(&self.ecs, context, self.options.seed)
+                    == GameOverResult::QuitToMenu
+                {
+                    new_run_state = self.quit_to_menu();
+                }
+            }
+            RunState::ShowTargeting { range, item } => {
+                let (result, target) = gui::ranged_target(self, context, range);
+                match result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        new_run_state =
+                            perform_command(&mut self.ecs, Command::UseItem { item, target });
+                    }
+                }
+            }
+            RunState::ShowRemoveItem => {
+                let (result, item) = gui::remove_item_menu(self, context);
+                match result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item = item.unwrap();
+                        new_run_state =
+                            perform_command(&mut self.ecs, Command::RemoveItem { item });
+                    }
+                }
+            }
+            RunState::ShowDropItem => {
+                let (result, item) = gui::drop_item_menu(self, context);
+                match result {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item = item.unwrap();
+                        new_run_state = perform_command(&mut self.ecs, Command::DropItem { item });
+                    }
+                }
+            }
+        }
+
+        self.set_run_state(new_run_state);
+    }
+}
+
+impl State {
+    // A world with every component registered and a fresh first level, sitting at the main menu
+    pub fn new(options: Options) -> std::io::Result<State> {
+        // Everything random about a run is drawn from this one generator, so a seed reproduces it
+        let random = RandomNumberGenerator::seeded(options.seed);
+        let log = match &options.log_file {
+            Some(path) => GameLog::with_file(path)?,
+            None => GameLog::new(),
+        };
+
+        let raws = RawMaster::load()
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+
+        let mut gs = State {
+            ecs: World::new(),
+            options,
+        };
+
+        gs.ecs.register::<Position>();
+        gs.ecs.register::<Renderable>();
+        gs.ecs.register::<Player>();
+        gs.ecs.register::<Viewshed>();
+        gs.ecs.register::<Monster>();
+        gs.ecs.register::<Name>();
+        gs.ecs.register::<BlocksTile>();
+        gs.ecs.register::<CombatStats>();
+        gs.ecs.register::<WantsToMelee>();
+        gs.ecs.register::<Initiative>();
+        gs.ecs.register::<MyTurn>();
+        gs.ecs.register::<Experience>();
+        gs.ecs.register::<GivesExperience>();
+        gs.ecs.register::<SufferDamage>();
+        gs.ecs.register::<KilledBy>();
+        gs.ecs.register::<MeleeDamageType>();
+        gs.ecs.register::<Resistances>();
+        gs.ecs.register::<Vulnerabilities>();
+        gs.ecs.register::<OnDeath>();
+        gs.ecs.register::<Corpse>();
+        gs.ecs.register::<Door>();
+        gs.ecs.register::<Hidden>();
+        gs.ecs.register::<EntryTrigger>();
+        gs.ecs.register::<EntityMoved>();
+        gs.ecs.register::<Teleports>();
+        gs.ecs.register::<Alarm>();
+        gs.ecs.register::<Alerted>();
+        gs.ecs.register::<LootTable>();
+        gs.ecs.register::<CombatRecord>();
+        gs.ecs.register::<Item>();
+        gs.ecs.register::<InBackpack>();
+        gs.ecs.register::<WantsToPickupItem>();
+        gs.ecs.register::<Consumable>();
+        gs.ecs.register::<ProvidesHealing>();
+        gs.ecs.register::<WantsToUseItem>();
+        gs.ecs.register::<WantsToDropItem>();
+        gs.ecs.register::<Ranged>();
+        gs.ecs.register::<InflictsDamage>();
+        gs.ecs.register::<AreaOfEffect>();
+        gs.ecs.register::<Confused>();
+        gs.ecs.register::<Poisoned>();
+        gs.ecs.register::<Regenerating>();
+        gs.ecs.register::<CausesConfusion>();
+        gs.ecs.register::<CausesPoison>();
+        gs.ecs.register::<ProvidesRegeneration>();
+        gs.ecs.register::<Equippable>();
+        gs.ecs.register::<Equipped>();
+        gs.ecs.register::<MeleePowerBonus>();
+        gs.ecs.register::<DefenseBonus>();
+        gs.ecs.register::<WantsToRemoveItem>();
+        gs.ecs.register::<SimpleMarker<SerializeMe>>();
+        gs.ecs.register::<SerializationHelper>();
+
+        gs.ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        gs.ecs.insert(raws);
+
+        gs.ecs.insert(random);
+        gs.ecs.insert(log);
+        gs.ecs.insert(TurnCounter(0));
+        gs.ecs.insert(MonsterCounter(0));
+        gs.ecs.insert(TargetCursor {
+            position: Point::new(0, 0),
+            mouse: Point::new(0, 0),
+        });
+
+        gs.ecs.insert(Point::new(0, 0));
+        gs.ecs.insert(RunState::MainMenu {
+            menu_selection: MainMenuSelection::NewGame,
+        });
+
+        gs.new_game();
+        Ok(gs)
+    }
+}
+
+pub fn run(options: Options) -> rltk::BError {
     use rltk::RltkBuilder;
     let context = RltkBuilder::simple80x50()
         .with_title("Roguelike Tutorial")
+        .with_advanced_input(true)
         .build()?;
-    let mut gs = State { ecs: World::new() };
-
-    gs.ecs.register::<Position>();
-    gs.ecs.register::<Renderable>();
-    gs.ecs.register::<Player>();
-    gs.ecs.register::<Viewshed>();
-    gs.ecs.register::<Monster>();
-    gs.ecs.register::<Name>();
-    gs.ecs.register::<BlocksTile>();
-    gs.ecs.register::<CombatStats>();
-    gs.ecs.register::<WantsToMelee>();
-    gs.ecs.register::<SufferDamage>();
-
-    let map = Map::new_map_rooms_and_corridors();
-    let (player_x, player_y) = map.rooms[0].center();
-
-    let player_entity = gs
-        .ecs
-        .create_entity()
-        .with(Position {
-            x: player_x,
-            y: player_y,
-        })
-        .with(Renderable {
-            glyph: rltk::to_cp437('@'),
-            foreground: RGB::named(rltk::YELLOW),
-            background: RGB::named(rltk::BLACK),
-        })
-        .with(Player {})
-        .with(Viewshed {
-            visible_tiles: Vec::new(),
-            range: PLAYER_VIEWSHED,
-            dirty: true,
-        })
-        .with(Name {
-            name: "Player".to_string(),
-        })
-        .with(CombatStats {
-            max_hp: 30,
-            hp: 30,
-            defense: 2,
-            power: 5,
-        })
-        .build();
-
-    let mut random = rltk::RandomNumberGenerator::new();
-    for (i, room) in map.rooms.iter().skip(1).enumerate() {
-        let (x, y) = room.center();
-
-        let glyph: rltk::FontCharType;
-        let name: String;
-        let roll = random.roll_dice(1, 2);
-
-        match roll {
-            1 => {
-                glyph = rltk::to_cp437('g');
-                name = "Goblin".to_string();
-            }
-            _ => {
-                glyph = rltk::to_cp437('o');
-                name = "Orc".to_string();
-            }
-        }
+    let mut gs = State::new(options)?;
 
-        gs.ecs
-            .create_entity()
-            .with(Position { x, y })
-            .with(Renderable {
-                glyph,
-                foreground: RGB::named(rltk::RED),
-                background: RGB::named(rltk::BLACK),
-            })
-            .with(Viewshed {
-                visible_tiles: Vec::new(),
-                range: 8,
-                dirty: true,
-            })
-            .with(Monster {})
-            .with(Name {
-                name: format!("{} #{}", &name, i),
-            })
-            .with(BlocksTile {})
-            .with(CombatStats {
-                max_hp: 16,
-                hp: 16,
-                defense: 1,
-                power: 4,
-            })
-            .build();
-    }
-
-    gs.ecs.insert(Point::new(player_x, player_y));
-
-    gs.ecs.insert(map);
-
-    gs.ecs.insert(player_entity);
-
-    gs.ecs.insert(RunState::PreRun);
+    // `--continue` skips the menu and goes straight back into the saved game
+    if gs.options.continue_game {
+        gs.continue_game()?;
+    }
 
     rltk::main_loop(context, gs)
 }
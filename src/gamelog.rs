@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use rltk::RGB;
+
+pub struct LogEntry {
+    pub time: Duration,
+    pub text: String,
+    pub colour: RGB,
+}
+
+impl LogEntry {
+    // Minutes and seconds since the log was started, e.g. `[03:27]`
+    pub fn timestamp(&self) -> String {
+        let seconds = self.time.as_secs();
+        format!("[{:02}:{:02}]", seconds / 60, seconds % 60)
+    }
+}
+
+pub struct GameLog {
+    pub entries: Vec<LogEntry>,
+    // How many entries back from the newest the log panel is scrolled
+    pub scroll: usize,
+    started: Instant,
+    file: Option<File>,
+}
+
+impl Default for GameLog {
+    fn default() -> Self {
+        GameLog::new()
+    }
+}
+
+impl GameLog {
+    pub fn new() -> GameLog {
+        GameLog {
+            entries: Vec::new(),
+            scroll: 0,
+            started: Instant::now(),
+            file: None,
+        }
+    }
+
+    // Every entry is also written to `path`, which is truncated first
+    pub fn with_file(path: &str) -> std::io::Result<GameLog> {
+        let mut log = GameLog::new();
+        log.file = Some(File::create(path)?);
+        Ok(log)
+    }
+
+    // Forgets every entry and restarts the clock, the file (if any) keeps everything
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.scroll = 0;
+        self.started = Instant::now();
+    }
+
+    pub fn log<S: ToString>(&mut self, text: S) {
+        self.push(text, RGB::named(rltk::WHITE));
+    }
+
+    pub fn push<S: ToString>(&mut self, text: S, colour: RGB) {
+        let entry = LogEntry {
+            time: self.started.elapsed(),
+            text: text.to_string(),
+            colour,
+        };
+
+        if let Some(file) = &mut self.file {
+            // The file is only a debugging aid, losing a line shouldn't stop the game
+            let _ = writeln!(file, "{} {}", entry.timestamp(), entry.text);
+        }
+
+        // Keep a scrolled back view looking at the same entries
+        if self.scroll > 0 {
+            self.scroll += 1;
+        }
+        self.entries.push(entry);
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = usize::min(self.scroll + lines, self.entries.len().saturating_sub(1));
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    // The `count` entries that should be on screen, oldest first
+    pub fn visible_entries(&self, count: usize) -> &[LogEntry] {
+        let end = self.entries.len() - usize::min(self.scroll, self.entries.len());
+        let start = end.saturating_sub(count);
+        &self.entries[start..end]
+    }
+}
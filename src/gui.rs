@@ -0,0 +1,490 @@
+use rltk::{DistanceAlg, Point, Rltk, VirtualKeyCode, RGB};
+use specs::prelude::*;
+
+use crate::components::{
+    CombatRecord, CombatStats, DamageType, DefenseBonus, Equipped, Experience, InBackpack,
+    KilledBy, MeleePowerBonus, Name, Viewshed,
+};
+use crate::game::{RunState, State, TurnCounter};
+use crate::gamelog::GameLog;
+use crate::map::{Map, MAP_SIZE_X, MAP_SIZE_Y};
+use crate::player::movement_delta;
+use crate::save_load_system;
+
+pub static PANEL_HEIGHT: i32 = 7;
+
+// Where the targeting cursor sits, it follows the mouse when that moves and the movement keys otherwise
+pub struct TargetCursor {
+    pub position: Point,
+    pub mouse: Point,
+}
+
+pub fn draw_ui(ecs: &World, context: &mut Rltk, seed: u64) {
+    context.draw_box(
+        0,
+        MAP_SIZE_Y,
+        MAP_SIZE_X - 1,
+        PANEL_HEIGHT - 1,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+
+    draw_stats(ecs, context);
+
+    let seed_text = format!(" Seed: {} ", seed);
+    context.print_color(
+        MAP_SIZE_X - 2 - seed_text.len() as i32,
+        MAP_SIZE_Y + PANEL_HEIGHT - 1,
+        RGB::named(rltk::GREY),
+        RGB::named(rltk::BLACK),
+        &seed_text,
+    );
+
+    let log = ecs.fetch::<GameLog>();
+    if log.scroll > 0 {
+        let scroll_text = format!(" {} newer (PgDn) ", log.scroll);
+        context.print_color(
+            MAP_SIZE_X - 2 - scroll_text.len() as i32,
+            MAP_SIZE_Y,
+            RGB::named(rltk::GREY),
+            RGB::named(rltk::BLACK),
+            &scroll_text,
+        );
+    }
+
+    let entries = log.visible_entries((PANEL_HEIGHT - 2) as usize);
+    for (y, entry) in (MAP_SIZE_Y + 1..).zip(entries) {
+        let timestamp = entry.timestamp();
+        context.print_color(
+            2,
+            y,
+            RGB::named(rltk::GREY),
+            RGB::named(rltk::BLACK),
+            &timestamp,
+        );
+        context.print_color(
+            3 + timestamp.len() as i32,
+            y,
+            entry.colour,
+            RGB::named(rltk::BLACK),
+            &entry.text,
+        );
+    }
+}
+
+// Depth, health and experience along the top edge of the panel, the rest of the player's stats along the bottom
+fn draw_stats(ecs: &World, context: &mut Rltk) {
+    const BAR_X: i32 = 28;
+    const BAR_WIDTH: i32 = 28;
+
+    let map = ecs.fetch::<Map>();
+    context.print_color(
+        2,
+        MAP_SIZE_Y,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        format!(" Depth: {} ", map.depth),
+    );
+
+    let player_entity = ecs.fetch::<Entity>();
+    let combat_stats = ecs.read_storage::<CombatStats>();
+    if let Some(stats) = combat_stats.get(*player_entity) {
+        let health = format!(" HP: {} / {} ", stats.hp, stats.max_hp);
+        context.print_color(
+            BAR_X - 1 - health.len() as i32,
+            MAP_SIZE_Y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            &health,
+        );
+
+        let bar_colour = if stats.hp * 2 > stats.max_hp {
+            RGB::named(rltk::GREEN)
+        } else if stats.hp * 4 > stats.max_hp {
+            RGB::named(rltk::YELLOW)
+        } else {
+            RGB::named(rltk::RED)
+        };
+        context.draw_bar_horizontal(
+            BAR_X,
+            MAP_SIZE_Y,
+            BAR_WIDTH,
+            stats.hp,
+            stats.max_hp,
+            bar_colour,
+            RGB::named(rltk::BLACK),
+        );
+
+        let equipped = ecs.read_storage::<Equipped>();
+        let power_bonuses = ecs.read_storage::<MeleePowerBonus>();
+        let defense_bonuses = ecs.read_storage::<DefenseBonus>();
+        let mut power = stats.power;
+        let mut defense = stats.defense;
+        for (item, bonus) in (&equipped, &power_bonuses).join() {
+            if item.owner == *player_entity {
+                power += bonus.power;
+            }
+        }
+        for (item, bonus) in (&equipped, &defense_bonuses).join() {
+            if item.owner == *player_entity {
+                defense += bonus.defense;
+            }
+        }
+        let turn = ecs.fetch::<TurnCounter>();
+        context.print_color(
+            2,
+            MAP_SIZE_Y + PANEL_HEIGHT - 1,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            format!(
+                " Damage: {}d{}{:+}  Defense: {}  Evasion: {}  Turn: {} ",
+                stats.damage.n_dice,
+                stats.damage.die_type,
+                stats.damage.bonus + power,
+                defense,
+                stats.evasion,
+                turn.0
+            ),
+        );
+
+        let experience = ecs.read_storage::<Experience>();
+        if let Some(experience) = experience.get(*player_entity) {
+            context.print_color(
+                BAR_X + BAR_WIDTH + 1,
+                MAP_SIZE_Y,
+                RGB::named(rltk::MAGENTA),
+                RGB::named(rltk::BLACK),
+                format!(
+                    " Level {}  XP {}/{} ",
+                    experience.level,
+                    experience.xp,
+                    experience.xp_to_next_level()
+                ),
+            );
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum ItemMenuResult {
+    Cancel,
+    NoResponse,
+    Selected,
+}
+
+pub fn show_inventory(gs: &mut State, context: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let inventory = carried_items(&gs.ecs);
+    item_menu(context, "Inventory", &inventory)
+}
+
+pub fn drop_item_menu(gs: &mut State, context: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let inventory = carried_items(&gs.ecs);
+    item_menu(context, "Drop Which Item?", &inventory)
+}
+
+pub fn remove_item_menu(gs: &mut State, context: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let worn = worn_items(&gs.ecs);
+    item_menu(context, "Remove Which Item?", &worn)
+}
+
+// The player's backpack in the order the menus letter it
+pub fn carried_items(ecs: &World) -> Vec<(Entity, String)> {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let entities = ecs.entities();
+
+    (&entities, &backpack, &names)
+        .join()
+        .filter(|(_, item, _)| item.owner == *player_entity)
+        .map(|(entity, _, name)| (entity, name.name.clone()))
+        .collect()
+}
+
+pub fn worn_items(ecs: &World) -> Vec<(Entity, String)> {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let entities = ecs.entities();
+
+    (&entities, &equipped, &names)
+        .join()
+        .filter(|(_, item, _)| item.owner == *player_entity)
+        .map(|(entity, _, name)| (entity, name.name.clone()))
+        .collect()
+}
+
+// Lists the items, one letter per item, and returns the item chosen
+fn item_menu(
+    context: &mut Rltk,
+    title: &str,
+    inventory: &[(Entity, String)],
+) -> (ItemMenuResult, Option<Entity>) {
+    let count = inventory.len() as i32;
+    let top = 25 - (count / 2);
+    context.draw_box(
+        15,
+        top - 2,
+        31,
+        count + 3,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    context.print_color(
+        18,
+        top - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        title,
+    );
+    context.print_color(
+        18,
+        top + count + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ESCAPE to cancel",
+    );
+
+    for (i, (_, name)) in inventory.iter().enumerate() {
+        let y = top + i as i32;
+        let letter = b'a' + i as u8;
+        context.set(
+            17,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('('),
+        );
+        context.set(
+            18,
+            y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            letter as rltk::FontCharType,
+        );
+        context.set(
+            19,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437(')'),
+        );
+        context.print(21, y, name);
+    }
+
+    match context.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(VirtualKeyCode::Escape) => (ItemMenuResult::Cancel, None),
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < count {
+                (
+                    ItemMenuResult::Selected,
+                    Some(inventory[selection as usize].0),
+                )
+            } else {
+                (ItemMenuResult::NoResponse, None)
+            }
+        }
+    }
+}
+
+// Highlights the tiles within `range` the player can see and lets them pick one
+pub fn ranged_target(
+    gs: &mut State,
+    context: &mut Rltk,
+    range: i32,
+) -> (ItemMenuResult, Option<Point>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let player_position = gs.ecs.fetch::<Point>();
+    let viewsheds = gs.ecs.read_storage::<Viewshed>();
+    let mut cursor = gs.ecs.fetch_mut::<TargetCursor>();
+
+    context.print_color(
+        5,
+        0,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Select Target: ENTER or click to confirm, ESCAPE to cancel",
+    );
+
+    let mut available_cells = Vec::new();
+    match viewsheds.get(*player_entity) {
+        None => return (ItemMenuResult::Cancel, None),
+        Some(visible) => {
+            for tile in visible.visible_tiles.iter() {
+                let distance = DistanceAlg::Pythagoras.distance2d(*player_position, *tile);
+                if distance <= range as f32 {
+                    context.set_bg(tile.x, tile.y, RGB::named(rltk::BLUE));
+                    available_cells.push(*tile);
+                }
+            }
+        }
+    }
+
+    let mouse = context.mouse_point();
+    if mouse != cursor.mouse {
+        cursor.mouse = mouse;
+        cursor.position = mouse;
+    }
+    if let Some((delta_x, delta_y)) = context.key.and_then(movement_delta) {
+        cursor.position.x = i32::max(0, i32::min(MAP_SIZE_X - 1, cursor.position.x + delta_x));
+        cursor.position.y = i32::max(0, i32::min(MAP_SIZE_Y - 1, cursor.position.y + delta_y));
+    }
+
+    let valid_target = available_cells.contains(&cursor.position);
+    let cursor_colour = if valid_target {
+        RGB::named(rltk::CYAN)
+    } else {
+        RGB::named(rltk::RED)
+    };
+    context.set_bg(cursor.position.x, cursor.position.y, cursor_colour);
+
+    let confirmed = context.left_click
+        || matches!(
+            context.key,
+            Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::Space)
+        );
+    if context.key == Some(VirtualKeyCode::Escape) {
+        (ItemMenuResult::Cancel, None)
+    } else if confirmed && valid_target {
+        (ItemMenuResult::Selected, Some(cursor.position))
+    } else {
+        (ItemMenuResult::NoResponse, None)
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MainMenuSelection {
+    NewGame,
+    Continue,
+    Quit,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum MainMenuResult {
+    NoSelection { selected: MainMenuSelection },
+    Selected { selected: MainMenuSelection },
+}
+
+// Continue is only offered when there is a save to load
+pub fn main_menu(gs: &mut State, context: &mut Rltk) -> MainMenuResult {
+    let mut entries = vec![(MainMenuSelection::NewGame, "New Game")];
+    if save_load_system::does_save_exist(&gs.options.save_file) {
+        entries.push((MainMenuSelection::Continue, "Continue"));
+    }
+    entries.push((MainMenuSelection::Quit, "Quit"));
+
+    let run_state = *gs.ecs.fetch::<RunState>();
+    let current = match run_state {
+        RunState::MainMenu { menu_selection } => menu_selection,
+        _ => MainMenuSelection::NewGame,
+    };
+    let index = entries
+        .iter()
+        .position(|(entry, _)| *entry == current)
+        .unwrap_or(0);
+
+    context.print_color_centered(
+        15,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Rust Roguelike Tutorial",
+    );
+    for (y, (i, (_, label))) in (24..).zip(entries.iter().enumerate()) {
+        let colour = if i == index {
+            RGB::named(rltk::MAGENTA)
+        } else {
+            RGB::named(rltk::WHITE)
+        };
+        context.print_color_centered(y, colour, RGB::named(rltk::BLACK), label);
+    }
+
+    let selected = entries[index].0;
+    match context.key {
+        Some(VirtualKeyCode::Up) => MainMenuResult::NoSelection {
+            selected: entries[(index + entries.len() - 1) % entries.len()].0,
+        },
+        Some(VirtualKeyCode::Down) => MainMenuResult::NoSelection {
+            selected: entries[(index + 1) % entries.len()].0,
+        },
+        Some(VirtualKeyCode::Return) => MainMenuResult::Selected { selected },
+        _ => MainMenuResult::NoSelection { selected },
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum GameOverResult {
+    NoSelection,
+    QuitToMenu,
+}
+
+// A summary of the run that just ended, any key goes back to the main menu
+pub fn game_over(ecs: &World, context: &mut Rltk, seed: u64) -> GameOverResult {
+    let depth = ecs.fetch::<Map>().depth;
+    let turns = ecs.fetch::<TurnCounter>().0;
+
+    context.print_color_centered(
+        15,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Your journey has ended!",
+    );
+    context.print_color_centered(
+        18,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        format!("You reached depth {} after {} turns.", depth, turns),
+    );
+    let player_entity = *ecs.fetch::<Entity>();
+    let killed_by = ecs.read_storage::<KilledBy>();
+    let names = ecs.read_storage::<Name>();
+    let cause = match killed_by.get(player_entity) {
+        Some(KilledBy {
+            killer: Some(killer),
+            ..
+        }) if *killer != player_entity => names
+            .get(*killer)
+            .map(|name| format!("You were killed by {}.", name.name)),
+        Some(KilledBy {
+            kind: DamageType::Poison,
+            ..
+        }) => Some("You succumbed to poison.".to_string()),
+        _ => None,
+    };
+    if let Some(cause) = cause {
+        context.print_color_centered(17, RGB::named(rltk::RED), RGB::named(rltk::BLACK), cause);
+    }
+
+    let record = ecs
+        .read_storage::<CombatRecord>()
+        .get(player_entity)
+        .cloned()
+        .unwrap_or_default();
+    context.print_color_centered(
+        19,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+        format!(
+            "You dealt {} damage and killed {} monsters.",
+            record.damage_dealt, record.kills
+        ),
+    );
+    context.print_color_centered(
+        20,
+        RGB::named(rltk::GREY),
+        RGB::named(rltk::BLACK),
+        format!("Seed: {}", seed),
+    );
+    context.print_color_centered(
+        22,
+        RGB::named(rltk::MAGENTA),
+        RGB::named(rltk::BLACK),
+        "Press any key to return to the menu.",
+    );
+
+    match context.key {
+        None => GameOverResult::NoSelection,
+        Some(_) => GameOverResult::QuitToMenu,
+    }
+}
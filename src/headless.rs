@@ -0,0 +1,228 @@
+use std::io::BufRead;
+
+use rltk::Point;
+use specs::prelude::*;
+
+use crate::components::{CombatStats, Ranged};
+use crate::game::{RunState, State, TurnCounter};
+use crate::gamelog::GameLog;
+use crate::gui;
+use crate::map::Map;
+use crate::options::Options;
+use crate::player::{perform_command, Command};
+
+// Drives the turn logic without a window, one player command at a time
+pub struct Headless {
+    pub state: State,
+}
+
+impl Headless {
+    // Starts a new game, or the saved one with `--continue`, ready for the first command
+    pub fn new(options: Options) -> std::io::Result<Headless> {
+        let mut state = State::new(options)?;
+        if state.options.continue_game {
+            state.continue_game()?;
+        } else {
+            state.set_run_state(RunState::PreRun);
+        }
+
+        let mut headless = Headless { state };
+        headless.settle();
+        Ok(headless)
+    }
+
+    pub fn world(&self) -> &World {
+        &self.state.ecs
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.state.ecs
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.state.run_state() == RunState::GameOver
+    }
+
+    // Plays `command` as the player's turn and lets everything else take theirs. The state that
+    // comes back is `AwaitingInput` again, unless the player died
+    pub fn step(&mut self, command: Command) -> RunState {
+        if self.state.run_state() != RunState::AwaitingInput {
+            return self.state.run_state();
+        }
+        let run_state = perform_command(&mut self.state.ecs, command);
+        self.state.set_run_state(run_state);
+        self.settle();
+        self.state.run_state()
+    }
+
+    // Waits out `turns` turns, stopping early if the player dies
+    pub fn advance(&mut self, turns: u32) -> RunState {
+        for _ in 0..turns {
+            if self.step(Command::Wait) != RunState::AwaitingInput {
+                break;
+            }
+        }
+        self.state.run_state()
+    }
+
+    // Plays one line of a script, see `run_script` for what a line can be
+    pub fn play_line(&mut self, line: &str) -> Result<RunState, String> {
+        Ok(match parse_line(self.world(), line)? {
+            ScriptLine::Command(command) => self.step(command),
+            ScriptLine::Wait(turns) => self.advance(turns),
+        })
+    }
+
+    // e.g. `Turn 12, depth 1, HP 24 / 30 at (40, 21)`
+    pub fn summary(&self) -> String {
+        let ecs = self.world();
+        let player_entity = *ecs.fetch::<Entity>();
+        let player_position = *ecs.fetch::<Point>();
+        let combat_stats = ecs.read_storage::<CombatStats>();
+        let (hp, max_hp) = combat_stats
+            .get(player_entity)
+            .map_or((0, 0), |stats| (stats.hp, stats.max_hp));
+
+        let mut summary = format!(
+            "Turn {}, depth {}, HP {} / {} at ({}, {})",
+            ecs.fetch::<TurnCounter>().0,
+            ecs.fetch::<Map>().depth,
+            hp,
+            max_hp,
+            player_position.x,
+            player_position.y
+        );
+        if self.is_game_over() {
+            summary.push_str(", the player is dead");
+        }
+        summary
+    }
+
+    // Runs the turn logic until it needs the player again
+    pub fn settle(&mut self) {
+        loop {
+            let run_state = self.state.run_state();
+            let next = self.state.advance(run_state);
+            // Ticking is the only state that can carry on without changing
+            if next == run_state && run_state != RunState::Ticking {
+                break;
+            }
+            self.state.set_run_state(next);
+        }
+    }
+}
+
+// One line of a script, e.g. `ne`, `wait 5`, `use b 40 20` or `drop a`
+enum ScriptLine {
+    Command(Command),
+    Wait(u32),
+}
+
+// Plays the commands read from `input`, one per line, printing the game log as it goes and a
+// summary at the end. Blank lines and lines starting with `#` are skipped
+pub fn run_script<R: BufRead>(options: Options, input: R) -> rltk::BError {
+    let mut headless = Headless::new(options)?;
+    let mut printed = print_log(headless.world(), 0);
+
+    for (number, line) in (1..).zip(input.lines()) {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        headless
+            .play_line(line)
+            .map_err(|error| format!("Line {}: {}", number, error))?;
+        printed = print_log(headless.world(), printed);
+
+        if headless.is_game_over() {
+            break;
+        }
+    }
+
+    println!("{}", headless.summary());
+    Ok(())
+}
+
+// Prints the log entries after the first `printed` and returns how many have been printed now
+fn print_log(ecs: &World, printed: usize) -> usize {
+    let log = ecs.fetch::<GameLog>();
+    for entry in log.entries.iter().skip(printed) {
+        println!("{}", entry.text);
+    }
+    log.entries.len()
+}
+
+fn parse_line(ecs: &World, line: &str) -> Result<ScriptLine, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().unwrap_or_default();
+
+    let direction = match name {
+        "n" => Some((0, -1)),
+        "s" => Some((0, 1)),
+        "e" => Some((1, 0)),
+        "w" => Some((-1, 0)),
+        "ne" => Some((1, -1)),
+        "nw" => Some((-1, -1)),
+        "se" => Some((1, 1)),
+        "sw" => Some((-1, 1)),
+        _ => None,
+    };
+    if let Some((delta_x, delta_y)) = direction {
+        return Ok(ScriptLine::Command(Command::Move { delta_x, delta_y }));
+    }
+
+    let command = match name {
+        "wait" => {
+            let turns = match words.next() {
+                None => 1,
+                Some(word) => parse_number(word)?,
+            };
+            return Ok(ScriptLine::Wait(turns));
+        }
+        "pickup" => Command::PickUp,
+        "descend" => Command::Descend,
+        "close" => Command::CloseDoor,
+        "use" => {
+            let item = pick_item(&gui::carried_items(ecs), words.next())?;
+            let target = match (words.next(), words.next()) {
+                (Some(x), Some(y)) => {
+                    Some(Point::new(parse_number::<i32>(x)?, parse_number::<i32>(y)?))
+                }
+                (None, None) => None,
+                _ => return Err("use needs both an x and a y to target".to_string()),
+            };
+            if target.is_none() && ecs.read_storage::<Ranged>().get(item).is_some() {
+                return Err("that item needs a target".to_string());
+            }
+            Command::UseItem { item, target }
+        }
+        "drop" => Command::DropItem {
+            item: pick_item(&gui::carried_items(ecs), words.next())?,
+        },
+        "remove" => Command::RemoveItem {
+            item: pick_item(&gui::worn_items(ecs), words.next())?,
+        },
+        _ => return Err(format!("Unknown command '{}'", name)),
+    };
+    Ok(ScriptLine::Command(command))
+}
+
+fn parse_number<T: std::str::FromStr>(word: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("Invalid number '{}'", word))
+}
+
+// Items are picked by the same letter the menus show them with
+fn pick_item(items: &[(Entity, String)], letter: Option<&str>) -> Result<Entity, String> {
+    let letter = letter.ok_or("Missing the item's letter")?;
+    let index = match letter.as_bytes() {
+        [byte @ b'a'..=b'z'] => (byte - b'a') as usize,
+        _ => return Err(format!("Invalid item letter '{}'", letter)),
+    };
+    items
+        .get(index)
+        .map(|(entity, _)| *entity)
+        .ok_or_else(|| format!("No item with the letter '{}'", letter))
+}
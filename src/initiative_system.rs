@@ -0,0 +1,54 @@
+use specs::prelude::*;
+
+use crate::components::{Initiative, MyTurn};
+use crate::game::RunState;
+
+// The energy an entity spends to take one action, so a speed of 100 is one action per tick
+pub const ACTION_COST: i32 = 100;
+
+// Decides who acts next. Everyone gains energy at their own speed until someone can afford an
+// action, those entities get `MyTurn` and the game waits for input once it's the player's
+pub struct InitiativeSystem {}
+
+impl<'a> System<'a> for InitiativeSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, RunState>,
+        WriteStorage<'a, Initiative>,
+        WriteStorage<'a, MyTurn>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, player_entity, mut runstate, mut initiatives, mut turns) = data;
+
+        if *runstate != RunState::Ticking {
+            return;
+        }
+
+        turns.clear();
+        loop {
+            let mut anyone_ready = false;
+            for (entity, initiative) in (&entities, &mut initiatives).join() {
+                if initiative.energy >= ACTION_COST {
+                    initiative.energy -= ACTION_COST;
+                    turns
+                        .insert(entity, MyTurn {})
+                        .expect("Unable to insert turn");
+                    anyone_ready = true;
+
+                    if entity == *player_entity {
+                        *runstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            if anyone_ready {
+                return;
+            }
+
+            for initiative in (&mut initiatives).join() {
+                initiative.energy += initiative.speed;
+            }
+        }
+    }
+}
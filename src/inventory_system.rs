@@ -0,0 +1,367 @@
+use rltk::RGB;
+use specs::prelude::*;
+
+use crate::components::{
+    AreaOfEffect, CausesConfusion, CausesPoison, CombatStats, Confused, Consumable, Equippable,
+    Equipped, InBackpack, InflictsDamage, Name, Poisoned, Position, ProvidesHealing,
+    ProvidesRegeneration, Regenerating, SufferDamage, WantsToDropItem, WantsToPickupItem,
+    WantsToRemoveItem, WantsToUseItem,
+};
+use crate::gamelog::GameLog;
+use crate::map::Map;
+
+pub struct ItemCollectionSystem {}
+
+impl<'a> System<'a> for ItemCollectionSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, WantsToPickupItem>,
+        WriteStorage<'a, Position>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, InBackpack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, mut log, mut wants_pickup, mut positions, names, mut backpack) = data;
+
+        for pickup in wants_pickup.join() {
+            positions.remove(pickup.item);
+            backpack
+                .insert(
+                    pickup.item,
+                    InBackpack {
+                        owner: pickup.collected_by,
+                    },
+                )
+                .expect("Unable to insert backpack entry");
+
+            if pickup.collected_by == *player_entity {
+                log.push(
+                    format!("You pick up the {}.", names.get(pickup.item).unwrap().name),
+                    RGB::named(rltk::CYAN),
+                );
+            }
+        }
+
+        wants_pickup.clear();
+    }
+}
+
+pub struct ItemUseSystem {}
+
+impl<'a> System<'a> for ItemUseSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, Map>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Consumable>,
+        ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, InflictsDamage>,
+        ReadStorage<'a, AreaOfEffect>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, CausesConfusion>,
+        ReadStorage<'a, CausesPoison>,
+        ReadStorage<'a, ProvidesRegeneration>,
+        WriteStorage<'a, Confused>,
+        WriteStorage<'a, Poisoned>,
+        WriteStorage<'a, Regenerating>,
+        ReadStorage<'a, Equippable>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InBackpack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut log,
+            map,
+            entities,
+            mut wants_use,
+            names,
+            consumables,
+            healing,
+            inflict_damage,
+            area_of_effect,
+            mut combat_stats,
+            mut suffer_damage,
+            causes_confusion,
+            causes_poison,
+            provides_regeneration,
+            mut confused,
+            mut poisoned,
+            mut regenerating,
+            equippable,
+            mut equipped,
+            mut backpack,
+        ) = data;
+
+        for (entity, use_item) in (&entities, &wants_use).join() {
+            let item_name = &names.get(use_item.item).unwrap().name;
+
+            // Equipping swaps out whatever the user already has in that slot
+            if let Some(can_equip) = equippable.get(use_item.item) {
+                let slot = can_equip.slot;
+                let to_unequip: Vec<Entity> = (&entities, &equipped)
+                    .join()
+                    .filter(|(_, item)| item.owner == entity && item.slot == slot)
+                    .map(|(item_entity, _)| item_entity)
+                    .collect();
+                for item in to_unequip {
+                    equipped.remove(item);
+                    backpack
+                        .insert(item, InBackpack { owner: entity })
+                        .expect("Unable to insert backpack entry");
+                    if entity == *player_entity {
+                        log.log(format!("You unequip {}.", names.get(item).unwrap().name));
+                    }
+                }
+
+                equipped
+                    .insert(
+                        use_item.item,
+                        Equipped {
+                            owner: entity,
+                            slot,
+                        },
+                    )
+                    .expect("Unable to insert equipped component");
+                backpack.remove(use_item.item);
+                if entity == *player_entity {
+                    log.log(format!("You equip {}.", item_name));
+                }
+            }
+
+            // Without a target the item is used on whoever is holding it
+            let mut targets: Vec<Entity> = Vec::new();
+            match use_item.target {
+                None => targets.push(entity),
+                Some(target) => match area_of_effect.get(use_item.item) {
+                    None => {
+                        let index = map.xy_index(target.x, target.y);
+                        targets.extend(map.tile_content[index].iter());
+                    }
+                    Some(area) => {
+                        let mut blast_tiles = rltk::field_of_view(target, area.radius, &*map);
+                        blast_tiles.retain(|point| {
+                            point.x > 0
+                                && point.x < map.width - 1
+                                && point.y > 0
+                                && point.y < map.height - 1
+                        });
+                        for tile in blast_tiles.iter() {
+                            let index = map.xy_index(tile.x, tile.y);
+                            targets.extend(map.tile_content[index].iter());
+                        }
+                    }
+                },
+            }
+
+            if let Some(healer) = healing.get(use_item.item) {
+                for target in targets.iter() {
+                    if let Some(stats) = combat_stats.get_mut(*target) {
+                        stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
+                        if entity == *player_entity {
+                            log.push(
+                                format!(
+                                    "You use the {}, healing {} hp.",
+                                    item_name, healer.heal_amount
+                                ),
+                                RGB::named(rltk::GREEN),
+                            );
+                        }
+                    }
+                }
+            }
+
+            if let Some(damage) = inflict_damage.get(use_item.item) {
+                for target in targets.iter() {
+                    if combat_stats.get(*target).is_none() {
+                        continue;
+                    }
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        *target,
+                        damage.damage,
+                        Some(entity),
+                        damage.kind,
+                    );
+                    if entity == *player_entity {
+                        log.push(
+                            format!(
+                                "You use {} on {}, inflicting {} hp.",
+                                item_name,
+                                names.get(*target).unwrap().name,
+                                damage.damage
+                            ),
+                            RGB::named(rltk::ORANGE),
+                        );
+                    }
+                }
+            }
+
+            // Status effects only take hold on things that can fight
+            let affected: Vec<Entity> = targets
+                .iter()
+                .filter(|target| combat_stats.get(**target).is_some())
+                .cloned()
+                .collect();
+
+            if let Some(confusion) = causes_confusion.get(use_item.item) {
+                for target in affected.iter() {
+                    confused
+                        .insert(
+                            *target,
+                            Confused {
+                                turns: confusion.turns,
+                            },
+                        )
+                        .expect("Unable to insert status");
+                    if entity == *player_entity {
+                        log.push(
+                            format!(
+                                "You use {} on {}, confusing them.",
+                                item_name,
+                                names.get(*target).unwrap().name
+                            ),
+                            RGB::named(rltk::MAGENTA),
+                        );
+                    }
+                }
+            }
+
+            if let Some(poison) = causes_poison.get(use_item.item) {
+                for target in affected.iter() {
+                    poisoned
+                        .insert(
+                            *target,
+                            Poisoned {
+                                turns: poison.turns,
+                                damage: poison.damage,
+                                source: Some(entity),
+                            },
+                        )
+                        .expect("Unable to insert status");
+                    if entity == *player_entity {
+                        log.push(
+                            format!(
+                                "You use {} on {}, poisoning them.",
+                                item_name,
+                                names.get(*target).unwrap().name
+                            ),
+                            RGB::named(rltk::GREEN),
+                        );
+                    }
+                }
+            }
+
+            if let Some(regeneration) = provides_regeneration.get(use_item.item) {
+                for target in affected.iter() {
+                    regenerating
+                        .insert(
+                            *target,
+                            Regenerating {
+                                turns: regeneration.turns,
+                                amount: regeneration.amount,
+                            },
+                        )
+                        .expect("Unable to insert status");
+                    if entity == *player_entity {
+                        log.push(
+                            format!("You use the {}, your wounds begin to close.", item_name),
+                            RGB::named(rltk::GREEN),
+                        );
+                    }
+                }
+            }
+
+            if consumables.get(use_item.item).is_some() {
+                entities
+                    .delete(use_item.item)
+                    .expect("Unable to delete consumed item");
+            }
+        }
+
+        wants_use.clear();
+    }
+}
+
+pub struct ItemDropSystem {}
+
+impl<'a> System<'a> for ItemDropSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToDropItem>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, InBackpack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, mut log, entities, mut wants_drop, names, mut positions, mut backpack) =
+            data;
+
+        for (entity, to_drop) in (&entities, &wants_drop).join() {
+            let dropper_position = match positions.get(entity) {
+                Some(position) => *position,
+                None => continue,
+            };
+            positions
+                .insert(to_drop.item, dropper_position)
+                .expect("Unable to insert position");
+            backpack.remove(to_drop.item);
+
+            if entity == *player_entity {
+                log.log(format!(
+                    "You drop the {}.",
+                    names.get(to_drop.item).unwrap().name
+                ));
+            }
+        }
+
+        wants_drop.clear();
+    }
+}
+
+pub struct ItemRemoveSystem {}
+
+impl<'a> System<'a> for ItemRemoveSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToRemoveItem>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InBackpack>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (player_entity, mut log, entities, mut wants_remove, names, mut equipped, mut backpack) =
+            data;
+
+        for (entity, to_remove) in (&entities, &wants_remove).join() {
+            equipped.remove(to_remove.item);
+            backpack
+                .insert(to_remove.item, InBackpack { owner: entity })
+                .expect("Unable to insert backpack entry");
+
+            if entity == *player_entity {
+                log.log(format!(
+                    "You unequip {}.",
+                    names.get(to_remove.item).unwrap().name
+                ));
+            }
+        }
+
+        wants_remove.clear();
+    }
+}
@@ -0,0 +1,23 @@
+pub mod components;
+pub mod damage_system;
+pub mod game;
+pub mod gamelog;
+pub mod gui;
+pub mod headless;
+pub mod initiative_system;
+pub mod inventory_system;
+pub mod map;
+pub mod map_builders;
+pub mod map_indexing_system;
+pub mod melee_combat_system;
+pub mod monster_ai_system;
+pub mod options;
+pub mod player;
+pub mod random_table;
+pub mod raws;
+pub mod rect;
+pub mod save_load_system;
+pub mod spawner;
+pub mod status_effect_system;
+pub mod trap_system;
+pub mod visibility_system;
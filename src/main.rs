@@ -1,14 +1,9 @@
-mod components;
-mod damage_system;
-mod game;
-mod map;
-mod map_indexing_system;
-mod melee_combat_system;
-mod monster_ai_system;
-mod player;
-mod rect;
-mod visibility_system;
+use rust_game::{game, headless, options};
 
 fn main() -> rltk::BError {
-    game::run()
+    let options = options::Options::from_args()?;
+    if options.headless {
+        return headless::run_script(options, std::io::stdin().lock());
+    }
+    game::run(options)
 }
@@ -1,20 +1,22 @@
-use std::cmp::{max, min};
-
-use rltk::{Algorithm2D, BaseMap, DistanceAlg, Point, RandomNumberGenerator, Rltk, SmallVec, RGB};
+use rltk::{Algorithm2D, BaseMap, DistanceAlg, Point, Rltk, SmallVec, RGB};
+use serde::{Deserialize, Serialize};
 use specs::shred::Fetch;
-use specs::{Entity, World};
+use specs::{Entity, World, WriteStorage};
 
+use crate::components::{Door, Renderable};
 use crate::rect::Rect;
 
 pub static MAP_SIZE_X: i32 = 80;
-pub static MAP_SIZE_Y: i32 = 50;
+pub static MAP_SIZE_Y: i32 = 43;
 
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
+    DownStairs,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Map {
     pub tiles: Vec<TileType>,
     pub rooms: Vec<Rect>,
@@ -23,7 +25,11 @@ pub struct Map {
     pub revealed_tiles: Vec<bool>,
     pub visible_tiles: Vec<bool>,
     pub blocked: Vec<bool>,
+    pub closed_doors: Vec<bool>,
+    // Rebuilt by the MapIndexingSystem every turn, so it isn't worth saving
+    #[serde(skip)]
     pub tile_content: Vec<Vec<Entity>>,
+    pub depth: i32,
 }
 
 impl Algorithm2D for Map {
@@ -34,7 +40,7 @@ impl Algorithm2D for Map {
 
 impl BaseMap for Map {
     fn is_opaque(&self, index: usize) -> bool {
-        self.tiles[index] == TileType::Wall
+        self.tiles[index] == TileType::Wall || self.closed_doors[index]
     }
 
     fn get_available_exits(&self, index: usize) -> SmallVec<[(usize, f32); 10]> {
@@ -86,35 +92,8 @@ impl Map {
         (y as usize * self.width as usize) + x as usize
     }
 
-    fn apply_room_to_map(&mut self, room: &Rect) {
-        for y in room.y1 + 1..=room.y2 {
-            for x in room.x1 + 1..=room.x2 {
-                let index = self.xy_index(x, y);
-                self.tiles[index] = TileType::Floor;
-            }
-        }
-    }
-
-    fn apply_horizontal_tunnel(&mut self, x1: i32, x2: i32, y: i32) {
-        for x in min(x1, x2)..=max(x1, x2) {
-            let index = self.xy_index(x, y);
-            if index > 0 && index < (self.width * self.height) as usize {
-                self.tiles[index] = TileType::Floor;
-            }
-        }
-    }
-
-    fn apply_vertical_tunnel(&mut self, y1: i32, y2: i32, x: i32) {
-        for y in min(y1, y2)..=max(y1, y2) {
-            let index = self.xy_index(x, y);
-            if index > 0 && index < (self.width * self.height) as usize {
-                self.tiles[index] = TileType::Floor;
-            }
-        }
-    }
-
-    pub fn new_map_rooms_and_corridors() -> Map {
-        let mut map = Map {
+    pub fn new(depth: i32) -> Map {
+        Map {
             tiles: vec![TileType::Wall; (MAP_SIZE_X * MAP_SIZE_Y) as usize],
             rooms: Vec::new(),
             width: MAP_SIZE_X,
@@ -122,46 +101,10 @@ impl Map {
             revealed_tiles: vec![false; (MAP_SIZE_X * MAP_SIZE_Y) as usize],
             visible_tiles: vec![false; (MAP_SIZE_X * MAP_SIZE_Y) as usize],
             blocked: vec![false; (MAP_SIZE_X * MAP_SIZE_Y) as usize],
+            closed_doors: vec![false; (MAP_SIZE_X * MAP_SIZE_Y) as usize],
             tile_content: vec![Vec::new(); (MAP_SIZE_X * MAP_SIZE_Y) as usize],
-        };
-
-        const MAX_ROOMS: i32 = 30;
-        const MIN_SIZE: i32 = 6;
-        const MAX_SIZE: i32 = 10;
-
-        let mut rng = RandomNumberGenerator::new();
-
-        for _ in 0..MAX_ROOMS {
-            let width = rng.range(MIN_SIZE, MAX_SIZE);
-            let height = rng.range(MIN_SIZE, MAX_SIZE);
-            let x = rng.roll_dice(1, map.width - width - 1) - 1;
-            let y = rng.roll_dice(1, map.height - height - 1) - 1;
-            let new_room = Rect::new(x, y, width, height);
-            let mut ok = true;
-            for other_room in map.rooms.iter() {
-                if new_room.intersect(other_room) {
-                    ok = false
-                }
-            }
-            if ok {
-                map.apply_room_to_map(&new_room);
-
-                if !map.rooms.is_empty() {
-                    let (new_x, new_y) = new_room.center();
-                    let (prev_x, prev_y) = map.rooms[map.rooms.len() - 1].center();
-                    if rng.range(0, 2) == 1 {
-                        map.apply_horizontal_tunnel(prev_x, new_x, prev_y);
-                        map.apply_vertical_tunnel(prev_y, new_y, new_x);
-                    } else {
-                        map.apply_horizontal_tunnel(prev_x, new_x, new_y);
-                        map.apply_vertical_tunnel(prev_y, new_y, prev_x);
-                    }
-                }
-
-                map.rooms.push(new_room);
-            }
+            depth,
         }
-        map
     }
 
     fn is_exit_valid(&self, x: i32, y: i32) -> bool {
@@ -169,7 +112,8 @@ impl Map {
             return false;
         }
         let index = self.xy_index(x, y);
-        !self.blocked[index]
+        // Closed doors are left in, whoever paths through one opens it when they get there
+        !self.blocked[index] || self.closed_doors[index]
     }
 
     pub fn populate_blocked(&mut self) {
@@ -185,6 +129,43 @@ impl Map {
     }
 }
 
+// Opens or closes `door`, which stands on the tile at `index`, straight away so
+// nothing has to wait for the MapIndexingSystem to see the change
+pub fn set_door(
+    door: Entity,
+    index: usize,
+    open: bool,
+    map: &mut Map,
+    doors: &mut WriteStorage<Door>,
+    renderables: &mut WriteStorage<Renderable>,
+) {
+    if let Some(state) = doors.get_mut(door) {
+        state.open = open;
+        if let Some(renderable) = renderables.get_mut(door) {
+            renderable.glyph = state.glyph();
+        }
+        map.blocked[index] = !open;
+        map.closed_doors[index] = !open;
+    }
+}
+
+// Opens whichever door is on the tile at `index`, returning false if there wasn't one
+pub fn open_door_at(
+    index: usize,
+    map: &mut Map,
+    doors: &mut WriteStorage<Door>,
+    renderables: &mut WriteStorage<Renderable>,
+) -> bool {
+    let door = map.tile_content[index]
+        .iter()
+        .copied()
+        .find(|entity| doors.get(*entity).is_some());
+    if let Some(door) = door {
+        set_door(door, index, true, map, doors, renderables);
+    }
+    door.is_some()
+}
+
 pub fn draw_map(ecs: &World, context: &mut Rltk) {
     let map: Fetch<Map> = ecs.fetch::<Map>();
 
@@ -203,6 +184,10 @@ pub fn draw_map(ecs: &World, context: &mut Rltk) {
                     glyph = rltk::to_cp437('#');
                     foreground = RGB::from_f32(0., 1.0, 0.);
                 }
+                TileType::DownStairs => {
+                    glyph = rltk::to_cp437('>');
+                    foreground = RGB::from_f32(0., 1.0, 1.0);
+                }
             }
             if !map.visible_tiles[index] {
                 foreground = foreground.to_greyscale();
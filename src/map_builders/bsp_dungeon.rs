@@ -0,0 +1,160 @@
+use rltk::RandomNumberGenerator;
+use specs::World;
+
+use super::common::{apply_room_to_map, draw_corridor, spawn_doors, spawn_room};
+use super::MapBuilder;
+use crate::components::Position;
+use crate::map::{Map, TileType};
+use crate::rect::Rect;
+
+// Binary space partitioning: keep splitting the map into quarters and drop a room into some of them
+pub struct BspDungeonBuilder {
+    map: Map,
+    rects: Vec<Rect>,
+}
+
+impl BspDungeonBuilder {
+    pub fn new(depth: i32) -> BspDungeonBuilder {
+        BspDungeonBuilder {
+            map: Map::new(depth),
+            rects: Vec::new(),
+        }
+    }
+
+    fn make_rooms(&mut self, rng: &mut RandomNumberGenerator) {
+        const MAX_ATTEMPTS: i32 = 240;
+
+        self.rects.clear();
+        self.rects
+            .push(Rect::new(2, 2, self.map.width - 5, self.map.height - 5));
+        let first_room = self.rects[0];
+        self.add_sub_rects(first_room);
+
+        for _ in 0..MAX_ATTEMPTS {
+            let rect = self.get_random_rect(rng);
+            let candidate = self.get_random_sub_rect(rect, rng);
+
+            if self.is_possible(candidate) {
+                apply_room_to_map(&mut self.map, &candidate);
+                self.map.rooms.push(candidate);
+                self.add_sub_rects(rect);
+            }
+        }
+
+        // Joining rooms left to right keeps the corridors from criss-crossing the whole map
+        self.map.rooms.sort_by_key(|room| room.x1);
+
+        for i in 0..self.map.rooms.len().saturating_sub(1) {
+            let room = self.map.rooms[i];
+            let next_room = self.map.rooms[i + 1];
+            let start_x = room.x1 + (rng.roll_dice(1, i32::abs(room.x1 - room.x2)) - 1);
+            let start_y = room.y1 + (rng.roll_dice(1, i32::abs(room.y1 - room.y2)) - 1);
+            let end_x =
+                next_room.x1 + (rng.roll_dice(1, i32::abs(next_room.x1 - next_room.x2)) - 1);
+            let end_y =
+                next_room.y1 + (rng.roll_dice(1, i32::abs(next_room.y1 - next_room.y2)) - 1);
+            draw_corridor(&mut self.map, start_x, start_y, end_x, end_y);
+        }
+
+        let (stairs_x, stairs_y) = self.map.rooms[self.map.rooms.len() - 1].center();
+        let stairs_index = self.map.xy_index(stairs_x, stairs_y);
+        self.map.tiles[stairs_index] = TileType::DownStairs;
+    }
+
+    fn add_sub_rects(&mut self, rect: Rect) {
+        let width = i32::abs(rect.x1 - rect.x2);
+        let height = i32::abs(rect.y1 - rect.y2);
+        let half_width = i32::max(width / 2, 1);
+        let half_height = i32::max(height / 2, 1);
+
+        self.rects
+            .push(Rect::new(rect.x1, rect.y1, half_width, half_height));
+        self.rects.push(Rect::new(
+            rect.x1,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+        self.rects.push(Rect::new(
+            rect.x1 + half_width,
+            rect.y1,
+            half_width,
+            half_height,
+        ));
+        self.rects.push(Rect::new(
+            rect.x1 + half_width,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+    }
+
+    fn get_random_rect(&mut self, rng: &mut RandomNumberGenerator) -> Rect {
+        if self.rects.len() == 1 {
+            return self.rects[0];
+        }
+        let index = (rng.roll_dice(1, self.rects.len() as i32) - 1) as usize;
+        self.rects[index]
+    }
+
+    fn get_random_sub_rect(&self, rect: Rect, rng: &mut RandomNumberGenerator) -> Rect {
+        let mut result = rect;
+        let rect_width = i32::abs(rect.x1 - rect.x2);
+        let rect_height = i32::abs(rect.y1 - rect.y2);
+
+        let width = i32::max(3, rng.roll_dice(1, i32::min(rect_width, 10)) - 1) + 1;
+        let height = i32::max(3, rng.roll_dice(1, i32::min(rect_height, 10)) - 1) + 1;
+
+        result.x1 += rng.roll_dice(1, 6) - 1;
+        result.y1 += rng.roll_dice(1, 6) - 1;
+        result.x2 = result.x1 + width;
+        result.y2 = result.y1 + height;
+
+        result
+    }
+
+    // A room fits if it, plus a one tile border, is solid wall and inside the map
+    fn is_possible(&self, rect: Rect) -> bool {
+        let mut expanded = rect;
+        expanded.x1 -= 2;
+        expanded.x2 += 2;
+        expanded.y1 -= 2;
+        expanded.y2 += 2;
+
+        for y in expanded.y1..=expanded.y2 {
+            for x in expanded.x1..=expanded.x2 {
+                if x > self.map.width - 2 || y > self.map.height - 2 || x < 1 || y < 1 {
+                    return false;
+                }
+                let index = self.map.xy_index(x, y);
+                if self.map.tiles[index] != TileType::Wall {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl MapBuilder for BspDungeonBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.make_rooms(rng);
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        let (x, y) = self.map.rooms[0].center();
+        Position { x, y }
+    }
+
+    fn spawn_entities(&mut self, ecs: &mut World) {
+        spawn_doors(ecs, &self.map);
+        for room in self.map.rooms.iter().skip(1) {
+            spawn_room(ecs, &self.map, room);
+        }
+    }
+}
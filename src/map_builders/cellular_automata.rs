@@ -0,0 +1,104 @@
+use rltk::RandomNumberGenerator;
+use specs::World;
+
+use super::common::{
+    find_starting_position, remove_unreachable_areas_returning_most_distant, spawn_on_random_floor,
+};
+use super::MapBuilder;
+use crate::components::Position;
+use crate::map::{Map, TileType};
+
+// Starts from noise and repeatedly smooths it by neighbour count, which leaves open caves
+pub struct CellularAutomataBuilder {
+    map: Map,
+    starting_position: Position,
+}
+
+impl CellularAutomataBuilder {
+    pub fn new(depth: i32) -> CellularAutomataBuilder {
+        CellularAutomataBuilder {
+            map: Map::new(depth),
+            starting_position: Position { x: 0, y: 0 },
+        }
+    }
+
+    fn seed_noise(&mut self, rng: &mut RandomNumberGenerator) {
+        for y in 1..self.map.height - 1 {
+            for x in 1..self.map.width - 1 {
+                let index = self.map.xy_index(x, y);
+                self.map.tiles[index] = if rng.roll_dice(1, 100) > 55 {
+                    TileType::Floor
+                } else {
+                    TileType::Wall
+                };
+            }
+        }
+    }
+
+    fn smooth(&mut self) {
+        let mut new_tiles = self.map.tiles.clone();
+        let width = self.map.width as usize;
+
+        for y in 1..self.map.height - 1 {
+            for x in 1..self.map.width - 1 {
+                let index = self.map.xy_index(x, y);
+                let neighbours = [
+                    index - 1,
+                    index + 1,
+                    index - width,
+                    index + width,
+                    index - width - 1,
+                    index - width + 1,
+                    index + width - 1,
+                    index + width + 1,
+                ]
+                .iter()
+                .filter(|n| self.map.tiles[**n] == TileType::Wall)
+                .count();
+
+                new_tiles[index] = if neighbours > 4 || neighbours == 0 {
+                    TileType::Wall
+                } else {
+                    TileType::Floor
+                };
+            }
+        }
+
+        self.map.tiles = new_tiles;
+    }
+}
+
+impl MapBuilder for CellularAutomataBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        const ITERATIONS: i32 = 15;
+
+        // A start in a pocket of its own would put the stairs under the player, so try again
+        let stairs_index = loop {
+            self.seed_noise(rng);
+            for _ in 0..ITERATIONS {
+                self.smooth();
+            }
+
+            self.starting_position = find_starting_position(&self.map);
+            if let Some(index) = remove_unreachable_areas_returning_most_distant(
+                &mut self.map,
+                &self.starting_position,
+            ) {
+                break index;
+            }
+        };
+        self.map.tiles[stairs_index] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position
+    }
+
+    fn spawn_entities(&mut self, ecs: &mut World) {
+        spawn_on_random_floor(ecs, &self.map, &self.starting_position);
+    }
+}
@@ -0,0 +1,175 @@
+use std::cmp::{max, min};
+
+use specs::prelude::*;
+
+use crate::components::Position;
+use crate::map::{Map, TileType};
+use crate::rect::Rect;
+use crate::spawner;
+
+pub fn apply_room_to_map(map: &mut Map, room: &Rect) {
+    for y in room.y1 + 1..=room.y2 {
+        for x in room.x1 + 1..=room.x2 {
+            let index = map.xy_index(x, y);
+            map.tiles[index] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_horizontal_tunnel(map: &mut Map, x1: i32, x2: i32, y: i32) {
+    for x in min(x1, x2)..=max(x1, x2) {
+        let index = map.xy_index(x, y);
+        if index > 0 && index < (map.width * map.height) as usize {
+            map.tiles[index] = TileType::Floor;
+        }
+    }
+}
+
+pub fn apply_vertical_tunnel(map: &mut Map, y1: i32, y2: i32, x: i32) {
+    for y in min(y1, y2)..=max(y1, y2) {
+        let index = map.xy_index(x, y);
+        if index > 0 && index < (map.width * map.height) as usize {
+            map.tiles[index] = TileType::Floor;
+        }
+    }
+}
+
+// Walks one step at a time from (x1, y1) towards (x2, y2), carving floor as it goes
+pub fn draw_corridor(map: &mut Map, x1: i32, y1: i32, x2: i32, y2: i32) {
+    let mut x = x1;
+    let mut y = y1;
+
+    while x != x2 || y != y2 {
+        if x < x2 {
+            x += 1;
+        } else if x > x2 {
+            x -= 1;
+        } else if y < y2 {
+            y += 1;
+        } else if y > y2 {
+            y -= 1;
+        }
+
+        let index = map.xy_index(x, y);
+        map.tiles[index] = TileType::Floor;
+    }
+}
+
+// The floor tile closest to the middle of the map
+pub fn find_starting_position(map: &Map) -> Position {
+    let (center_x, center_y) = (map.width / 2, map.height / 2);
+    let index = map
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == TileType::Floor)
+        .map(|(index, _)| index as i32)
+        .min_by_key(|index| {
+            let x = index % map.width;
+            let y = index / map.width;
+            (x - center_x).pow(2) + (y - center_y).pow(2)
+        })
+        .expect("Map has no floor to start on");
+    Position {
+        x: index % map.width,
+        y: index / map.width,
+    }
+}
+
+// Turns any floor the player couldn't walk to from `start` back into wall,
+// returning the reachable tile furthest from `start`, or None if `start` is walled in
+pub fn remove_unreachable_areas_returning_most_distant(
+    map: &mut Map,
+    start: &Position,
+) -> Option<usize> {
+    map.populate_blocked();
+    let start_index = map.xy_index(start.x, start.y);
+    let dijkstra_map = rltk::DijkstraMap::new(
+        map.width as usize,
+        map.height as usize,
+        &[start_index],
+        &*map,
+        200.0,
+    );
+    let mut exit_index = None;
+    let mut exit_distance = 0.0f32;
+    for (index, tile) in map.tiles.iter_mut().enumerate() {
+        if *tile == TileType::Floor {
+            let distance = dijkstra_map.map[index];
+            if distance == f32::MAX {
+                *tile = TileType::Wall;
+            } else if distance > exit_distance {
+                exit_index = Some(index);
+                exit_distance = distance;
+            }
+        }
+    }
+    exit_index
+}
+
+// Spawns from the spawn table across the room's floor
+pub fn spawn_room(ecs: &mut World, map: &Map, room: &Rect) {
+    let mut area = Vec::new();
+    for y in room.y1 + 1..=room.y2 {
+        for x in room.x1 + 1..=room.x2 {
+            area.push(map.xy_index(x, y));
+        }
+    }
+    spawner::spawn_region(ecs, map, &area);
+}
+
+// Puts a door wherever a corridor passes through the wall around one of the rooms
+pub fn spawn_doors(ecs: &mut World, map: &Map) {
+    let is_wall = |x: i32, y: i32| map.tiles[map.xy_index(x, y)] == TileType::Wall;
+    let mut doorways = Vec::new();
+    for room in map.rooms.iter() {
+        let mut around = Vec::new();
+        for x in room.x1..=room.x2 + 1 {
+            around.push((x, room.y1));
+            around.push((x, room.y2 + 1));
+        }
+        for y in room.y1 + 1..=room.y2 {
+            around.push((room.x1, y));
+            around.push((room.x2 + 1, y));
+        }
+
+        for (x, y) in around {
+            if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+                continue;
+            }
+            // Only a gap with wall either side of it, not a corridor running along the room
+            let doorway = map.tiles[map.xy_index(x, y)] == TileType::Floor
+                && ((is_wall(x - 1, y) && is_wall(x + 1, y))
+                    || (is_wall(x, y - 1) && is_wall(x, y + 1)));
+            if doorway && !doorways.contains(&(x, y)) {
+                doorways.push((x, y));
+            }
+        }
+    }
+
+    for (x, y) in doorways {
+        spawner::door(ecs, x, y);
+    }
+}
+
+// Used by builders without rooms, the map is cut into square sectors that are each
+// spawned into like a room, leaving out the start
+pub fn spawn_on_random_floor(ecs: &mut World, map: &Map, start: &Position) {
+    const SECTOR_SIZE: i32 = 12;
+
+    let start_index = map.xy_index(start.x, start.y);
+    for sector_y in (0..map.height).step_by(SECTOR_SIZE as usize) {
+        for sector_x in (0..map.width).step_by(SECTOR_SIZE as usize) {
+            let mut area = Vec::new();
+            for y in sector_y..i32::min(sector_y + SECTOR_SIZE, map.height) {
+                for x in sector_x..i32::min(sector_x + SECTOR_SIZE, map.width) {
+                    let index = map.xy_index(x, y);
+                    if index != start_index {
+                        area.push(index);
+                    }
+                }
+            }
+            spawner::spawn_region(ecs, map, &area);
+        }
+    }
+}
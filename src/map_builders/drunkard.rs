@@ -0,0 +1,85 @@
+use rltk::RandomNumberGenerator;
+use specs::World;
+
+use super::common::{remove_unreachable_areas_returning_most_distant, spawn_on_random_floor};
+use super::MapBuilder;
+use crate::components::Position;
+use crate::map::{Map, TileType};
+
+// Sends diggers stumbling randomly from the start until enough of the map is floor
+pub struct DrunkardsWalkBuilder {
+    map: Map,
+    starting_position: Position,
+}
+
+impl DrunkardsWalkBuilder {
+    pub fn new(depth: i32) -> DrunkardsWalkBuilder {
+        DrunkardsWalkBuilder {
+            map: Map::new(depth),
+            starting_position: Position { x: 0, y: 0 },
+        }
+    }
+
+    fn walk(&mut self, rng: &mut RandomNumberGenerator) {
+        const DRUNKARD_LIFETIME: i32 = 400;
+        const FLOOR_PERCENT: usize = 50;
+
+        let total_tiles = (self.map.width * self.map.height) as usize;
+        let desired_floor_tiles = total_tiles * FLOOR_PERCENT / 100;
+
+        let start_index = self
+            .map
+            .xy_index(self.starting_position.x, self.starting_position.y);
+        self.map.tiles[start_index] = TileType::Floor;
+
+        let mut floor_tiles = 1;
+        while floor_tiles < desired_floor_tiles {
+            let mut x = self.starting_position.x;
+            let mut y = self.starting_position.y;
+
+            for _ in 0..DRUNKARD_LIFETIME {
+                let index = self.map.xy_index(x, y);
+                if self.map.tiles[index] == TileType::Wall {
+                    self.map.tiles[index] = TileType::Floor;
+                    floor_tiles += 1;
+                }
+
+                match rng.roll_dice(1, 4) {
+                    1 if x > 2 => x -= 1,
+                    2 if x < self.map.width - 2 => x += 1,
+                    3 if y > 2 => y -= 1,
+                    4 if y < self.map.height - 2 => y += 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl MapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.starting_position = Position {
+            x: self.map.width / 2,
+            y: self.map.height / 2,
+        };
+        // Everything dug out is reachable from the start, so the walk always leaves somewhere
+        // else for the stairs
+        self.walk(rng);
+        let stairs_index =
+            remove_unreachable_areas_returning_most_distant(&mut self.map, &self.starting_position)
+                .expect("The drunkards dug nothing but the start");
+        self.map.tiles[stairs_index] = TileType::DownStairs;
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        self.starting_position
+    }
+
+    fn spawn_entities(&mut self, ecs: &mut World) {
+        spawn_on_random_floor(ecs, &self.map, &self.starting_position);
+    }
+}
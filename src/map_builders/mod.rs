@@ -0,0 +1,42 @@
+use rltk::RandomNumberGenerator;
+use specs::World;
+
+use crate::components::Position;
+use crate::map::Map;
+
+mod bsp_dungeon;
+mod cellular_automata;
+mod common;
+mod drunkard;
+mod simple_map;
+
+use bsp_dungeon::BspDungeonBuilder;
+use cellular_automata::CellularAutomataBuilder;
+use drunkard::DrunkardsWalkBuilder;
+use simple_map::SimpleMapBuilder;
+
+pub static BUILDER_NAMES: [&str; 4] = ["rooms", "bsp", "cellular", "drunkard"];
+
+pub trait MapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator);
+    fn get_map(&self) -> Map;
+    fn get_starting_position(&self) -> Position;
+    fn spawn_entities(&mut self, ecs: &mut World);
+}
+
+pub fn builder_by_name(name: &str, depth: i32) -> Option<Box<dyn MapBuilder>> {
+    match name {
+        "rooms" => Some(Box::new(SimpleMapBuilder::new(depth))),
+        "bsp" => Some(Box::new(BspDungeonBuilder::new(depth))),
+        "cellular" => Some(Box::new(CellularAutomataBuilder::new(depth))),
+        "drunkard" => Some(Box::new(DrunkardsWalkBuilder::new(depth))),
+        _ => None,
+    }
+}
+
+pub fn random_builder(rng: &mut RandomNumberGenerator, depth: i32) -> Box<dyn MapBuilder> {
+    let name = rng
+        .random_slice_entry(&BUILDER_NAMES)
+        .expect("No map builders registered");
+    builder_by_name(name, depth).unwrap()
+}
@@ -0,0 +1,85 @@
+use rltk::RandomNumberGenerator;
+use specs::World;
+
+use super::common::{
+    apply_horizontal_tunnel, apply_room_to_map, apply_vertical_tunnel, spawn_doors, spawn_room,
+};
+use super::MapBuilder;
+use crate::components::Position;
+use crate::map::{Map, TileType};
+use crate::rect::Rect;
+
+pub struct SimpleMapBuilder {
+    map: Map,
+}
+
+impl SimpleMapBuilder {
+    pub fn new(depth: i32) -> SimpleMapBuilder {
+        SimpleMapBuilder {
+            map: Map::new(depth),
+        }
+    }
+
+    fn rooms_and_corridors(&mut self, rng: &mut RandomNumberGenerator) {
+        const MAX_ROOMS: i32 = 30;
+        const MIN_SIZE: i32 = 6;
+        const MAX_SIZE: i32 = 10;
+
+        for _ in 0..MAX_ROOMS {
+            let width = rng.range(MIN_SIZE, MAX_SIZE);
+            let height = rng.range(MIN_SIZE, MAX_SIZE);
+            let x = rng.roll_dice(1, self.map.width - width - 1) - 1;
+            let y = rng.roll_dice(1, self.map.height - height - 1) - 1;
+            let new_room = Rect::new(x, y, width, height);
+            let mut ok = true;
+            for other_room in self.map.rooms.iter() {
+                if new_room.intersect(other_room) {
+                    ok = false
+                }
+            }
+            if ok {
+                apply_room_to_map(&mut self.map, &new_room);
+
+                if !self.map.rooms.is_empty() {
+                    let (new_x, new_y) = new_room.center();
+                    let (prev_x, prev_y) = self.map.rooms[self.map.rooms.len() - 1].center();
+                    if rng.range(0, 2) == 1 {
+                        apply_horizontal_tunnel(&mut self.map, prev_x, new_x, prev_y);
+                        apply_vertical_tunnel(&mut self.map, prev_y, new_y, new_x);
+                    } else {
+                        apply_horizontal_tunnel(&mut self.map, prev_x, new_x, new_y);
+                        apply_vertical_tunnel(&mut self.map, prev_y, new_y, prev_x);
+                    }
+                }
+
+                self.map.rooms.push(new_room);
+            }
+        }
+
+        let (stairs_x, stairs_y) = self.map.rooms[self.map.rooms.len() - 1].center();
+        let stairs_index = self.map.xy_index(stairs_x, stairs_y);
+        self.map.tiles[stairs_index] = TileType::DownStairs;
+    }
+}
+
+impl MapBuilder for SimpleMapBuilder {
+    fn build_map(&mut self, rng: &mut RandomNumberGenerator) {
+        self.rooms_and_corridors(rng);
+    }
+
+    fn get_map(&self) -> Map {
+        self.map.clone()
+    }
+
+    fn get_starting_position(&self) -> Position {
+        let (x, y) = self.map.rooms[0].center();
+        Position { x, y }
+    }
+
+    fn spawn_entities(&mut self, ecs: &mut World) {
+        spawn_doors(ecs, &self.map);
+        for room in self.map.rooms.iter().skip(1) {
+            spawn_room(ecs, &self.map, room);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::components::{BlocksTile, Position};
+use crate::components::{BlocksTile, Door, Position};
 use crate::map::Map;
 use specs::prelude::*;
 
@@ -9,19 +9,27 @@ impl<'a> System<'a> for MapIndexingSystem {
         WriteExpect<'a, Map>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, BlocksTile>,
+        ReadStorage<'a, Door>,
         Entities<'a>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, position, blockers, entities) = data;
+        let (mut map, position, blockers, doors, entities) = data;
         map.populate_blocked();
+        map.closed_doors.fill(false);
         map.clear_content_index();
 
         for (entity, position) in (&entities, &position).join() {
             let index = map.xy_index(position.x, position.y);
 
             // If they block, update the blocking list
-            blockers.get(entity).map(|_| map.blocked[index] = true);
+            if blockers.get(entity).is_some() {
+                map.blocked[index] = true;
+            }
+            if doors.get(entity).is_some_and(|door| !door.open) {
+                map.blocked[index] = true;
+                map.closed_doors[index] = true;
+            }
 
             map.tile_content[index].push(entity);
         }
@@ -1,22 +1,51 @@
-use crate::components::{CombatStats, Name, SufferDamage, WantsToMelee};
-use rltk::console;
+use crate::components::{
+    CombatStats, DefenseBonus, Equipped, MeleeDamageType, MeleePowerBonus, Name, SufferDamage,
+    WantsToMelee,
+};
+use crate::gamelog::GameLog;
+use crate::raws::RawMaster;
+use rltk::{RandomNumberGenerator, RGB};
 use specs::prelude::*;
 
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         Entities<'a>,
         WriteStorage<'a, WantsToMelee>,
         ReadStorage<'a, Name>,
         ReadStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, MeleeDamageType>,
+        ReadExpect<'a, RawMaster>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadExpect<'a, Entity>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut wants_melee, names, combat_stats, mut inflict_damage) = data;
+        let (
+            entities,
+            mut wants_melee,
+            names,
+            combat_stats,
+            mut inflict_damage,
+            mut log,
+            equipped,
+            power_bonuses,
+            defense_bonuses,
+            damage_types,
+            raws,
+            mut rng,
+            player_entity,
+        ) = data;
+        let rules = &raws.raws.combat;
 
-        for (_entity, wants_melee, name, stats) in
+        for (entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
             if stats.hp > 0 {
@@ -24,20 +53,101 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 if target_stats.hp > 0 {
                     let target_name = names.get(wants_melee.target).unwrap();
 
-                    let damage = i32::max(0, stats.power - target_stats.defense);
+                    let offensive_bonus: i32 = (&equipped, &power_bonuses)
+                        .join()
+                        .filter(|(item, _)| item.owner == entity)
+                        .map(|(_, bonus)| bonus.power)
+                        .sum();
+                    let defensive_bonus: i32 = (&equipped, &defense_bonuses)
+                        .join()
+                        .filter(|(item, _)| item.owner == wants_melee.target)
+                        .map(|(_, bonus)| bonus.defense)
+                        .sum();
+                    let kind = (&equipped, &damage_types)
+                        .join()
+                        .find(|(item, _)| item.owner == entity)
+                        .map_or(stats.damage_type, |(_, weapon)| weapon.kind);
+
+                    // Both rolls are always made, so a seed plays out the same whatever the outcome
+                    let attack_roll = rng.roll_dice(1, 20);
+                    let rolled =
+                        i32::max(0, rng.roll(stats.damage) + stats.power + offensive_bonus);
+
+                    if attack_roll <= rules.fumble_roll {
+                        let damage = (rolled as f32 * rules.fumble_multiplier) as i32;
+                        let is_player = entity == *player_entity;
+                        if damage == 0 {
+                            let message = match is_player {
+                                true => "You fumble the attack.".to_string(),
+                                false => format!("{} fumbles the attack.", &name.name),
+                            };
+                            log.push(message, RGB::named(rltk::GREY));
+                        } else {
+                            let message = match is_player {
+                                true => format!("You fumble and hurt yourself, for {} hp.", damage),
+                                false => format!(
+                                    "{} fumbles and hurts itself, for {} hp.",
+                                    &name.name, damage
+                                ),
+                            };
+                            log.push(message, RGB::named(rltk::ORANGE));
+                            SufferDamage::new_damage(
+                                &mut inflict_damage,
+                                entity,
+                                damage,
+                                Some(entity),
+                                kind,
+                            );
+                        }
+                        continue;
+                    }
+
+                    let critical = attack_roll >= rules.crit_roll;
+                    if !critical && attack_roll < rules.hit_target + target_stats.evasion {
+                        log.push(
+                            format!("{} misses {}.", &name.name, &target_name.name),
+                            RGB::named(rltk::GREY),
+                        );
+                        continue;
+                    }
+
+                    let rolled = match critical {
+                        true => (rolled as f32 * rules.crit_multiplier) as i32,
+                        false => rolled,
+                    };
+                    let damage = i32::max(0, rolled - (target_stats.defense + defensive_bonus));
 
                     if damage == 0 {
-                        console::log(&format!(
-                            "{} is unable to hurt {}",
-                            &name.name, &target_name.name
-                        ));
+                        log.push(
+                            format!("{} is unable to hurt {}", &name.name, &target_name.name),
+                            RGB::named(rltk::GREY),
+                        );
+                        continue;
+                    }
+                    if critical {
+                        log.push(
+                            format!(
+                                "{} critically hits {}, for {} hp!",
+                                &name.name, &target_name.name, damage
+                            ),
+                            RGB::named(rltk::YELLOW),
+                        );
                     } else {
-                        console::log(&format!(
-                            "{} hits {}, for {} hp.",
-                            &name.name, &target_name.name, damage
-                        ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
+                        log.push(
+                            format!(
+                                "{} hits {}, for {} hp.",
+                                &name.name, &target_name.name, damage
+                            ),
+                            RGB::named(rltk::ORANGE),
+                        );
                     }
+                    SufferDamage::new_damage(
+                        &mut inflict_damage,
+                        wants_melee.target,
+                        damage,
+                        Some(entity),
+                        kind,
+                    );
                 }
             }
         }
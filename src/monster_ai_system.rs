@@ -1,10 +1,11 @@
-use rltk::{DistanceAlg, Point};
+use rltk::{DistanceAlg, Point, RandomNumberGenerator};
 use specs::prelude::*;
 
-use crate::components::{Monster, Position, Viewshed, WantsToMelee};
-use crate::game::RunState;
-use crate::game::RunState::MonsterTurn;
-use crate::map::Map;
+use crate::components::{
+    Alerted, Confused, Door, EntityMoved, Monster, MyTurn, Position, Renderable, Viewshed,
+    WantsToMelee,
+};
+use crate::map::{self, Map};
 
 pub struct MonsterAI {}
 
@@ -14,12 +15,18 @@ impl<'a> System<'a> for MonsterAI {
         WriteExpect<'a, Map>,
         ReadExpect<'a, Point>,
         ReadExpect<'a, Entity>,
-        ReadExpect<'a, RunState>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
+        ReadStorage<'a, Confused>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, MyTurn>,
+        WriteStorage<'a, Door>,
+        WriteStorage<'a, Renderable>,
+        WriteStorage<'a, Alerted>,
+        WriteStorage<'a, EntityMoved>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -27,21 +34,54 @@ impl<'a> System<'a> for MonsterAI {
             mut map,
             player_pos,
             player_entity,
-            runstate,
             entities,
             mut viewshed,
             monster,
             mut position,
             mut wants_to_melee,
+            confused,
+            mut rng,
+            mut turns,
+            mut doors,
+            mut renderables,
+            mut alerted,
+            mut moved,
         ) = data;
+        let mut opened_door = false;
 
-        if *runstate != MonsterTurn {
-            return;
-        }
-
-        for (entity, mut viewshed, _monster, mut pos) in
-            (&entities, &mut viewshed, &monster, &mut position).join()
+        // Only the monsters the scheduler picked get to act, and only the once
+        for (entity, viewshed, _monster, pos, _turn) in
+            (&entities, &mut viewshed, &monster, &mut position, &turns).join()
         {
+            // Confused monsters stagger in a random direction instead of hunting the player
+            if confused.get(entity).is_some() {
+                let x = pos.x + rng.range(-1, 2);
+                let y = pos.y + rng.range(-1, 2);
+                if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 {
+                    let destination = map.xy_index(x, y);
+                    if map.closed_doors[destination] {
+                        opened_door |=
+                            map::open_door_at(destination, &mut map, &mut doors, &mut renderables);
+                    } else if !map.blocked[destination] {
+                        let index = map.xy_index(pos.x, pos.y);
+                        map.blocked[index] = false;
+                        pos.x = x;
+                        pos.y = y;
+                        map.blocked[destination] = true;
+                        viewshed.dirty = true;
+                        moved
+                            .insert(entity, EntityMoved {})
+                            .expect("Unable to insert movement");
+                    }
+                }
+                continue;
+            }
+
+            let sees_player = viewshed.visible_tiles.contains(&*player_pos);
+            if sees_player {
+                alerted.remove(entity);
+            }
+
             let distance =
                 DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
             if distance < 1.5 {
@@ -53,14 +93,26 @@ impl<'a> System<'a> for MonsterAI {
                         },
                     )
                     .expect("Unable to insert attack");
-            } else if viewshed.visible_tiles.contains(&*player_pos) {
-                // Path to the player
+            } else {
+                // Hunts the player when it can see them, otherwise heads for any alarm it heard
+                let target = match sees_player {
+                    true => Some(*player_pos),
+                    false => alerted
+                        .get(entity)
+                        .map(|alert| Point::new(alert.x, alert.y)),
+                };
+                let Some(target) = target else {
+                    continue;
+                };
                 let path = rltk::a_star_search(
                     map.xy_index(pos.x, pos.y),
-                    map.xy_index(player_pos.x, player_pos.y),
-                    &mut *map,
+                    map.xy_index(target.x, target.y),
+                    &*map,
                 );
-                if path.success && path.steps.len() > 1 {
+                if path.success && path.steps.len() > 1 && map.closed_doors[path.steps[1]] {
+                    opened_door |=
+                        map::open_door_at(path.steps[1], &mut map, &mut doors, &mut renderables);
+                } else if path.success && path.steps.len() > 1 {
                     let mut idx = map.xy_index(pos.x, pos.y);
                     map.blocked[idx] = false;
                     pos.x = path.steps[1] as i32 % map.width;
@@ -68,8 +120,22 @@ impl<'a> System<'a> for MonsterAI {
                     idx = map.xy_index(pos.x, pos.y);
                     map.blocked[idx] = true;
                     viewshed.dirty = true;
+                    moved
+                        .insert(entity, EntityMoved {})
+                        .expect("Unable to insert movement");
+                } else {
+                    // Either it got there or there's no way through
+                    alerted.remove(entity);
                 }
             }
         }
+
+        turns.clear();
+
+        if opened_door {
+            for viewshed in (&mut viewshed).join() {
+                viewshed.dirty = true;
+            }
+        }
     }
 }
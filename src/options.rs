@@ -0,0 +1,74 @@
+use rltk::RandomNumberGenerator;
+
+use crate::map_builders::BUILDER_NAMES;
+use crate::save_load_system::SAVE_PATH;
+
+pub struct Options {
+    pub seed: u64,
+    pub builder: Option<String>,
+    pub log_file: Option<String>,
+    pub save_file: String,
+    pub continue_game: bool,
+    pub headless: bool,
+}
+
+impl Options {
+    // Reads the command line, falling back to a random seed when `--seed` isn't given.
+    // Without `--builder` the map builder is picked by the seeded RNG.
+    // `--log-file` mirrors the in game message log to a file for debugging.
+    // `--save-file` saves somewhere other than `savegame.json`.
+    // `--continue` picks up the saved game instead of starting a new one.
+    // `--headless` plays the commands read from stdin without opening a window.
+    pub fn from_args() -> Result<Options, String> {
+        let mut seed = None;
+        let mut builder = None;
+        let mut log_file = None;
+        let mut save_file = None;
+        let mut continue_game = false;
+        let mut headless = false;
+        let mut args = std::env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or("--seed requires a value")?;
+                    let parsed = value
+                        .parse::<u64>()
+                        .map_err(|_| format!("Invalid seed '{}'", value))?;
+                    seed = Some(parsed);
+                }
+                "--builder" => {
+                    let value = args.next().ok_or("--builder requires a value")?;
+                    if !BUILDER_NAMES.contains(&value.as_str()) {
+                        return Err(format!(
+                            "Unknown builder '{}', expected one of {}",
+                            value,
+                            BUILDER_NAMES.join(", ")
+                        ));
+                    }
+                    builder = Some(value);
+                }
+                "--log-file" => {
+                    let value = args.next().ok_or("--log-file requires a path")?;
+                    log_file = Some(value);
+                }
+                "--save-file" => {
+                    let value = args.next().ok_or("--save-file requires a path")?;
+                    save_file = Some(value);
+                }
+                "--continue" => continue_game = true,
+                "--headless" => headless = true,
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+
+        Ok(Options {
+            seed: seed.unwrap_or_else(|| RandomNumberGenerator::new().next_u64()),
+            builder,
+            log_file,
+            save_file: save_file.unwrap_or_else(|| SAVE_PATH.to_string()),
+            continue_game,
+            headless,
+        })
+    }
+}
@@ -3,19 +3,27 @@ use std::cmp::{max, min};
 use rltk::{Point, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 
-use crate::components::{CombatStats, Player, Position, Viewshed, WantsToMelee};
+use crate::components::{
+    BlocksTile, CombatStats, Door, EntityMoved, Item, Player, Position, Renderable, Viewshed,
+    WantsToDropItem, WantsToMelee, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
+};
 use crate::game::{RunState, State};
-use crate::map::{Map, MAP_SIZE_X, MAP_SIZE_Y};
+use crate::gamelog::GameLog;
+use crate::map::{self, Map, TileType, MAP_SIZE_X, MAP_SIZE_Y};
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let mut positions = ecs.write_storage::<Position>();
     let mut players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
     let mut player_position = ecs.write_resource::<Point>();
-    let map = ecs.fetch::<Map>();
+    let mut map = ecs.fetch_mut::<Map>();
     let combat_stats = ecs.read_storage::<CombatStats>();
     let entities = ecs.entities();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
+    let mut doors = ecs.write_storage::<Door>();
+    let mut renderables = ecs.write_storage::<Renderable>();
+    let mut moved = ecs.write_storage::<EntityMoved>();
+    let mut opened_door = false;
 
     for (entity, _player, position, viewshed) in
         (&entities, &mut players, &mut positions, &mut viewsheds).join()
@@ -29,7 +37,7 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
         }
         let destination_index = map.xy_index(position.x + delta_x, position.y + delta_y);
         for potential_target in map.tile_content[destination_index].iter() {
-            combat_stats.get(*potential_target).map(|_| {
+            if combat_stats.get(*potential_target).is_some() {
                 wants_to_melee
                     .insert(
                         entity,
@@ -39,9 +47,13 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
                     )
                     .expect("Add target failed");
                 return; // So we don't move after attacking
-            });
+            }
         }
-        if !map.blocked[destination_index] {
+        // Bumping into a closed door opens it, which takes the move
+        if map.closed_doors[destination_index] {
+            opened_door =
+                map::open_door_at(destination_index, &mut map, &mut doors, &mut renderables);
+        } else if !map.blocked[destination_index] {
             position.x = min(MAP_SIZE_X - 1, max(0, position.x + delta_x));
             position.y = min(MAP_SIZE_Y - 1, max(0, position.y + delta_y));
 
@@ -49,40 +61,206 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             player_position.y = position.y;
 
             viewshed.dirty = true;
+            moved
+                .insert(entity, EntityMoved {})
+                .expect("Unable to insert movement");
+        }
+    }
+
+    // Whoever could see the door may now see through it
+    if opened_door {
+        for viewshed in (&mut viewsheds).join() {
+            viewshed.dirty = true;
+        }
+    }
+}
+
+// Closes every open door next to the player with nothing standing in the way,
+// returning whether there were any
+fn close_doors(ecs: &mut World) -> bool {
+    let player_position = *ecs.fetch::<Point>();
+    let mut map = ecs.fetch_mut::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let blockers = ecs.read_storage::<BlocksTile>();
+    let items = ecs.read_storage::<Item>();
+    let entities = ecs.entities();
+    let mut doors = ecs.write_storage::<Door>();
+    let mut renderables = ecs.write_storage::<Renderable>();
+
+    let mut closing = Vec::new();
+    for (entity, door, position) in (&entities, &doors, &positions).join() {
+        let next_to_player = (position.x - player_position.x).abs() <= 1
+            && (position.y - player_position.y).abs() <= 1
+            && (position.x, position.y) != (player_position.x, player_position.y);
+        if door.open && next_to_player {
+            closing.push((entity, map.xy_index(position.x, position.y)));
+        }
+    }
+    closing.retain(|(_, index)| {
+        !map.tile_content[*index]
+            .iter()
+            .any(|entity| blockers.get(*entity).is_some() || items.get(*entity).is_some())
+    });
+
+    for (door, index) in closing.iter() {
+        map::set_door(*door, *index, false, &mut map, &mut doors, &mut renderables);
+    }
+    if !closing.is_empty() {
+        for viewshed in (&mut ecs.write_storage::<Viewshed>()).join() {
+            viewshed.dirty = true;
+        }
+    }
+    !closing.is_empty()
+}
+
+pub fn try_next_level(ecs: &mut World) -> bool {
+    let player_position = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let player_index = map.xy_index(player_position.x, player_position.y);
+    if map.tiles[player_index] == TileType::DownStairs {
+        true
+    } else {
+        let mut log = ecs.fetch_mut::<GameLog>();
+        log.log("There is no way down from here.");
+        false
+    }
+}
+
+// Returns whether there was anything under the player to pick up
+fn get_item(ecs: &mut World) -> bool {
+    let player_position = ecs.fetch::<Point>();
+    let player_entity = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+
+    let target_item = (&entities, &items, &positions)
+        .join()
+        .find(|(_, _, position)| position.x == player_position.x && position.y == player_position.y)
+        .map(|(item_entity, _, _)| item_entity);
+
+    let Some(item) = target_item else {
+        return false;
+    };
+    let mut pickup = ecs.write_storage::<WantsToPickupItem>();
+    pickup
+        .insert(
+            *player_entity,
+            WantsToPickupItem {
+                collected_by: *player_entity,
+                item,
+            },
+        )
+        .expect("Unable to insert want to pickup");
+    true
+}
+
+// Everything the player can do with their turn, whether it came from the keyboard or a script
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Command {
+    Move { delta_x: i32, delta_y: i32 },
+    Wait,
+    PickUp,
+    Descend,
+    CloseDoor,
+    UseItem { item: Entity, target: Option<Point> },
+    DropItem { item: Entity },
+    RemoveItem { item: Entity },
+}
+
+// Carries out `command` for the player and returns the state the game should move to
+pub fn perform_command(ecs: &mut World, command: Command) -> RunState {
+    match command {
+        Command::Move { delta_x, delta_y } => try_move_player(delta_x, delta_y, ecs),
+        Command::Wait => {}
+        Command::PickUp => {
+            if !get_item(ecs) {
+                ecs.fetch_mut::<GameLog>()
+                    .log("There is nothing here to pick up.");
+                return RunState::AwaitingInput;
+            }
+        }
+        Command::Descend => {
+            if try_next_level(ecs) {
+                return RunState::NextLevel;
+            }
+            return RunState::AwaitingInput;
         }
+        Command::CloseDoor => {
+            if !close_doors(ecs) {
+                ecs.fetch_mut::<GameLog>()
+                    .log("There is no open door next to you to close.");
+                return RunState::AwaitingInput;
+            }
+        }
+        Command::UseItem { item, target } => insert_intent(ecs, WantsToUseItem { item, target }),
+        Command::DropItem { item } => insert_intent(ecs, WantsToDropItem { item }),
+        Command::RemoveItem { item } => insert_intent(ecs, WantsToRemoveItem { item }),
+    }
+    RunState::PlayerTurn
+}
+
+fn insert_intent<T: Component>(ecs: &mut World, intent: T) {
+    let player_entity = *ecs.fetch::<Entity>();
+    ecs.write_storage::<T>()
+        .insert(player_entity, intent)
+        .expect("Unable to insert intent");
+}
+
+// The direction a movement key points in, shared by walking and by the targeting cursor
+pub fn movement_delta(key: VirtualKeyCode) -> Option<(i32, i32)> {
+    match key {
+        VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::A => Some((-1, 0)),
+        VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::D => Some((1, 0)),
+        VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::W => Some((0, -1)),
+        VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::S => Some((0, 1)),
+        VirtualKeyCode::Numpad9 | VirtualKeyCode::E => Some((1, -1)),
+        VirtualKeyCode::Numpad7 | VirtualKeyCode::Q => Some((-1, -1)),
+        VirtualKeyCode::Numpad3 | VirtualKeyCode::X => Some((1, 1)),
+        VirtualKeyCode::Numpad1 | VirtualKeyCode::Z => Some((-1, 1)),
+        _ => None,
     }
 }
 
 pub fn player_input(gs: &mut State, context: &mut Rltk) -> RunState {
-    match context.key {
+    let key = match context.key {
         None => return RunState::AwaitingInput, // Nothing happened here
-        Some(key) => match key {
-            VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::A => {
-                try_move_player(-1, 0, &mut gs.ecs)
-            }
+        Some(key) => key,
+    };
 
-            VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::D => {
-                try_move_player(1, 0, &mut gs.ecs)
-            }
+    if let Some((delta_x, delta_y)) = movement_delta(key) {
+        return perform_command(&mut gs.ecs, Command::Move { delta_x, delta_y });
+    }
 
-            VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::W => {
-                try_move_player(0, -1, &mut gs.ecs)
-            }
+    match key {
+        VirtualKeyCode::G => perform_command(&mut gs.ecs, Command::PickUp),
 
-            VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::S => {
-                try_move_player(0, 1, &mut gs.ecs)
-            }
+        VirtualKeyCode::C => perform_command(&mut gs.ecs, Command::CloseDoor),
+
+        VirtualKeyCode::Space | VirtualKeyCode::Numpad5 => {
+            perform_command(&mut gs.ecs, Command::Wait)
+        }
+
+        VirtualKeyCode::I => RunState::ShowInventory,
 
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::E => try_move_player(1, -1, &mut gs.ecs),
+        VirtualKeyCode::R => RunState::ShowDropItem,
 
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::Q => try_move_player(-1, -1, &mut gs.ecs),
+        VirtualKeyCode::U => RunState::ShowRemoveItem,
 
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::X => try_move_player(1, 1, &mut gs.ecs),
+        VirtualKeyCode::Escape => RunState::SaveGame,
 
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::Z => try_move_player(-1, 1, &mut gs.ecs),
+        VirtualKeyCode::PageUp => {
+            gs.ecs.fetch_mut::<GameLog>().scroll_up(1);
+            RunState::AwaitingInput
+        }
 
-            _ => return RunState::AwaitingInput,
-        },
+        VirtualKeyCode::PageDown => {
+            gs.ecs.fetch_mut::<GameLog>().scroll_down(1);
+            RunState::AwaitingInput
+        }
+
+        VirtualKeyCode::Period => perform_command(&mut gs.ecs, Command::Descend),
+
+        _ => RunState::AwaitingInput,
     }
-    RunState::PlayerTurn
 }
@@ -0,0 +1,38 @@
+use rltk::RandomNumberGenerator;
+
+// Picks a name with a chance proportional to its weight
+#[derive(Default)]
+pub struct RandomTable {
+    entries: Vec<(String, i32)>,
+    total_weight: i32,
+}
+
+impl RandomTable {
+    pub fn new() -> RandomTable {
+        RandomTable::default()
+    }
+
+    // Entries without a positive weight can never come up, so they're left out
+    pub fn add<S: ToString>(mut self, name: S, weight: i32) -> RandomTable {
+        if weight > 0 {
+            self.total_weight += weight;
+            self.entries.push((name.to_string(), weight));
+        }
+        self
+    }
+
+    pub fn roll(&self, rng: &mut RandomNumberGenerator) -> Option<&str> {
+        if self.total_weight == 0 {
+            return None;
+        }
+
+        let mut roll = rng.roll_dice(1, self.total_weight) - 1;
+        for (name, weight) in self.entries.iter() {
+            if roll < *weight {
+                return Some(name);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
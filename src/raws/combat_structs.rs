@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+// Tuning for melee, every attack is a d20 roll
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CombatRaw {
+    // The roll that hits a target with no evasion, each point of evasion adds one to it
+    pub hit_target: i32,
+    // Rolls this high always hit, and the damage rolled is multiplied
+    pub crit_roll: i32,
+    pub crit_multiplier: f32,
+    // Rolls this low always miss, and the attacker takes this share of the damage rolled itself
+    pub fumble_roll: i32,
+    pub fumble_multiplier: f32,
+    // Applied to damage of a type the victim resists, or is vulnerable to
+    pub resistance_multiplier: f32,
+    pub vulnerability_multiplier: f32,
+}
@@ -0,0 +1,52 @@
+use serde::Deserialize;
+
+use super::RenderableRaw;
+use crate::components::{DamageType, EquipmentSlot};
+
+// Every field besides the name and look is optional, each one present adds the matching component
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ItemRaw {
+    pub name: String,
+    pub renderable: RenderableRaw,
+    #[serde(default)]
+    pub consumable: bool,
+    pub ranged: Option<i32>,
+    pub inflicts_damage: Option<i32>,
+    // For `inflicts_damage`, which is magic otherwise, or a melee weapon
+    pub damage_type: Option<DamageType>,
+    pub area_of_effect: Option<i32>,
+    pub provides_healing: Option<i32>,
+    pub causes_confusion: Option<i32>,
+    pub causes_poison: Option<PoisonRaw>,
+    pub provides_regeneration: Option<RegenerationRaw>,
+    pub equippable: Option<EquippableRaw>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PoisonRaw {
+    pub turns: i32,
+    pub damage: i32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RegenerationRaw {
+    pub turns: i32,
+    pub amount: i32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EquippableRaw {
+    pub slot: EquipmentSlot,
+    #[serde(default)]
+    pub power_bonus: i32,
+    #[serde(default)]
+    pub defense_bonus: i32,
+    #[serde(default)]
+    pub resistances: Vec<DamageType>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<DamageType>,
+}
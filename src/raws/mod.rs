@@ -0,0 +1,370 @@
+use std::collections::HashSet;
+
+use rltk::{DiceType, RGB};
+use serde::Deserialize;
+
+use crate::components::{DamageType, DeathEffect, EquipmentSlot};
+
+mod combat_structs;
+mod item_structs;
+mod monster_structs;
+mod spawn_table_structs;
+mod trap_structs;
+
+pub use combat_structs::*;
+pub use item_structs::*;
+pub use monster_structs::*;
+pub use spawn_table_structs::*;
+pub use trap_structs::*;
+
+// Built into the binary, so editing raws/spawns.json needs a rebuild to take effect
+static SPAWNS: &str = include_str!("../../raws/spawns.json");
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Raws {
+    pub combat: CombatRaw,
+    pub monsters: Vec<MonsterRaw>,
+    pub items: Vec<ItemRaw>,
+    #[serde(default)]
+    pub traps: Vec<TrapRaw>,
+    pub spawn_table: Vec<SpawnTableEntry>,
+}
+
+// Colours are HTML style hex codes, e.g. `#FF0000`, the background defaults to black
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RenderableRaw {
+    pub glyph: String,
+    pub foreground: String,
+    pub background: Option<String>,
+    pub render_order: i32,
+}
+
+impl RenderableRaw {
+    pub fn glyph(&self) -> rltk::FontCharType {
+        rltk::to_cp437(self.glyph.chars().next().unwrap_or(' '))
+    }
+
+    pub fn foreground(&self) -> RGB {
+        RGB::from_hex(&self.foreground).unwrap_or_else(|_| RGB::named(rltk::WHITE))
+    }
+
+    pub fn background(&self) -> RGB {
+        match &self.background {
+            Some(code) => RGB::from_hex(code).unwrap_or_else(|_| RGB::named(rltk::BLACK)),
+            None => RGB::named(rltk::BLACK),
+        }
+    }
+}
+
+// The templates everything outside the player is spawned from
+pub struct RawMaster {
+    pub raws: Raws,
+}
+
+impl RawMaster {
+    // The definitions built into the game
+    pub fn load() -> Result<RawMaster, String> {
+        RawMaster::from_json(SPAWNS)
+            .map_err(|error| format!("built in raws/spawns.json: {}", error))
+    }
+
+    // Parses and checks `json`, listing every bad entry rather than stopping at the first
+    pub fn from_json(json: &str) -> Result<RawMaster, String> {
+        let raws: Raws = serde_json::from_str(json).map_err(|error| error.to_string())?;
+
+        let errors = validate(&raws);
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+        Ok(RawMaster { raws })
+    }
+
+    pub fn monster(&self, name: &str) -> Option<&MonsterRaw> {
+        self.raws
+            .monsters
+            .iter()
+            .find(|monster| monster.name == name)
+    }
+
+    pub fn item(&self, name: &str) -> Option<&ItemRaw> {
+        self.raws.items.iter().find(|item| item.name == name)
+    }
+
+    pub fn trap(&self, name: &str) -> Option<&TrapRaw> {
+        self.raws.traps.iter().find(|trap| trap.name == name)
+    }
+}
+
+// Dice like `1d6` or `2d4+1`
+pub fn parse_dice(text: &str) -> Result<DiceType, String> {
+    let invalid = || format!("damage '{}' isn't dice like 1d6 or 2d4+1", text);
+    let well_formed = text
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, 'd' | '+' | '-'));
+    match rltk::parse_dice_string(text) {
+        Ok(dice) if well_formed && dice.n_dice > 0 && dice.die_type > 0 => Ok(dice),
+        _ => Err(invalid()),
+    }
+}
+
+fn validate(raws: &Raws) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut names = HashSet::new();
+
+    let combat = &raws.combat;
+    let mut error = |message: &str| errors.push(format!("combat: {}", message));
+    if !(1..=20).contains(&combat.fumble_roll) || !(1..=20).contains(&combat.crit_roll) {
+        error("crit_roll and fumble_roll must be rolls on a d20");
+    }
+    if combat.fumble_roll >= combat.crit_roll {
+        error("fumble_roll must be below crit_roll");
+    }
+    if combat.crit_multiplier < 1.0 {
+        error("crit_multiplier must be at least 1");
+    }
+    if combat.fumble_multiplier < 0.0 {
+        error("fumble_multiplier can't be negative");
+    }
+    if !(0.0..=1.0).contains(&combat.resistance_multiplier) {
+        error("resistance_multiplier must be between 0 and 1");
+    }
+    if combat.vulnerability_multiplier < 1.0 {
+        error("vulnerability_multiplier must be at least 1");
+    }
+
+    for (i, monster) in raws.monsters.iter().enumerate() {
+        let mut error = |message: &str| {
+            errors.push(format!("monsters[{}] \"{}\": {}", i, monster.name, message));
+        };
+        if !names.insert(monster.name.as_str()) {
+            error("the name is already taken");
+        }
+        if let Err(message) = validate_renderable(&monster.renderable) {
+            error(&message);
+        }
+        if monster.stats.max_hp < 1 {
+            error("max_hp must be at least 1");
+        }
+        if monster.stats.power < 0 || monster.stats.defense < 0 || monster.stats.evasion < 0 {
+            error("power, defense and evasion can't be negative");
+        }
+        if let Err(message) = parse_dice(&monster.stats.damage) {
+            error(&message);
+        }
+        if monster.vision_range < 1 {
+            error("vision_range must be at least 1");
+        }
+        if monster.speed < 1 {
+            error("speed must be at least 1");
+        }
+        if monster.xp < 0 {
+            error("xp can't be negative");
+        }
+        if let Err(message) = validate_resistances(&monster.resistances, &monster.vulnerabilities) {
+            error(&message);
+        }
+    }
+
+    for (i, item) in raws.items.iter().enumerate() {
+        let mut error = |message: &str| {
+            errors.push(format!("items[{}] \"{}\": {}", i, item.name, message));
+        };
+        if !names.insert(item.name.as_str()) {
+            error("the name is already taken");
+        }
+        if let Err(message) = validate_renderable(&item.renderable) {
+            error(&message);
+        }
+        if item.ranged.is_some_and(|range| range < 1) {
+            error("ranged must be a range of at least 1");
+        }
+        if item.area_of_effect.is_some_and(|radius| radius < 1) {
+            error("area_of_effect must be a radius of at least 1");
+        }
+        if item.provides_healing.is_some_and(|amount| amount < 1) {
+            error("provides_healing must be at least 1");
+        }
+        let turns = [
+            item.causes_confusion,
+            item.causes_poison.as_ref().map(|poison| poison.turns),
+            item.provides_regeneration.as_ref().map(|regen| regen.turns),
+        ];
+        if turns.iter().flatten().any(|&turns| turns < 1) {
+            error("confusion, poison and regeneration must last at least 1 turn");
+        }
+        let targets_others = item.inflicts_damage.is_some()
+            || item.causes_confusion.is_some()
+            || item.causes_poison.is_some();
+        if targets_others && item.ranged.is_none() {
+            error("damage, confusion and poison need a ranged item to aim them");
+        }
+        if item.area_of_effect.is_some() && item.ranged.is_none() {
+            error("area_of_effect needs a ranged item to aim it");
+        }
+        if item.equippable.is_some() && item.consumable {
+            error("an equippable item can't also be consumable");
+        }
+        let is_weapon = item
+            .equippable
+            .as_ref()
+            .is_some_and(|equippable| equippable.slot == EquipmentSlot::Melee);
+        if item.damage_type.is_some() && item.inflicts_damage.is_none() && !is_weapon {
+            error("damage_type needs inflicts_damage or a melee weapon");
+        }
+        if let Some(equippable) = &item.equippable {
+            if let Err(message) =
+                validate_resistances(&equippable.resistances, &equippable.vulnerabilities)
+            {
+                error(&message);
+            }
+        }
+    }
+
+    for (i, trap) in raws.traps.iter().enumerate() {
+        let mut error = |message: &str| {
+            errors.push(format!("traps[{}] \"{}\": {}", i, trap.name, message));
+        };
+        if !names.insert(trap.name.as_str()) {
+            error("the name is already taken");
+        }
+        if let Err(message) = validate_renderable(&trap.renderable) {
+            error(&message);
+        }
+        if trap.inflicts_damage.is_none() && !trap.teleports && trap.alarm.is_none() {
+            error("a trap needs inflicts_damage, teleports or alarm to do anything");
+        }
+        if trap.inflicts_damage.is_some_and(|damage| damage < 1) {
+            error("inflicts_damage must be at least 1");
+        }
+        if trap.damage_type.is_some() && trap.inflicts_damage.is_none() {
+            error("damage_type needs inflicts_damage");
+        }
+        if trap.alarm.is_some_and(|radius| radius < 1) {
+            error("alarm must be a radius of at least 1");
+        }
+    }
+
+    // Checked once every name is known, a monster can split into one defined after it
+    for (i, monster) in raws.monsters.iter().enumerate() {
+        for effect in monster.on_death.iter() {
+            let message = match effect {
+                DeathEffect::Explode { radius, damage, .. } if *radius < 0 || *damage < 0 => {
+                    "an explosion's radius and damage can't be negative"
+                }
+                DeathEffect::Split { into, .. } if *into == monster.name => {
+                    "a monster can't split into more of itself"
+                }
+                DeathEffect::Split { into, .. }
+                    if raws.monsters.iter().all(|m| m.name != *into) =>
+                {
+                    "there's no monster to split into with that name"
+                }
+                DeathEffect::Split { count, .. } if *count < 1 => "count must be at least 1",
+                _ => continue,
+            };
+            errors.push(format!(
+                "monsters[{}] \"{}\": on_death: {}",
+                i, monster.name, message
+            ));
+        }
+    }
+
+    for (i, monster) in raws.monsters.iter().enumerate() {
+        let mut error = |message: String| {
+            errors.push(format!("monsters[{}] \"{}\": {}", i, monster.name, message));
+        };
+        for drop in monster.loot.iter() {
+            if raws.items.iter().all(|item| item.name != drop.item) {
+                error(format!("loot: there's no item called '{}'", drop.item));
+            }
+            if !(1..=100).contains(&drop.chance) {
+                error(format!(
+                    "loot: '{}' chance must be from 1 to 100",
+                    drop.item
+                ));
+            }
+            if drop.quantity < 1 {
+                error(format!("loot: '{}' quantity must be at least 1", drop.item));
+            }
+        }
+
+        let mut slots = Vec::new();
+        for name in monster.equipment.iter() {
+            let equippable = raws
+                .items
+                .iter()
+                .find(|item| item.name == *name)
+                .and_then(|item| item.equippable.as_ref());
+            match equippable {
+                None => error(format!("equipment: '{}' isn't an equippable item", name)),
+                Some(equippable) if slots.contains(&equippable.slot) => {
+                    error(format!("equipment: '{}' needs a slot already in use", name))
+                }
+                Some(equippable) => slots.push(equippable.slot),
+            }
+        }
+    }
+
+    for (i, entry) in raws.spawn_table.iter().enumerate() {
+        let mut error = |message: &str| {
+            errors.push(format!(
+                "spawn_table[{}] \"{}\": {}",
+                i, entry.name, message
+            ));
+        };
+        if !names.contains(entry.name.as_str()) {
+            error("there's no monster, item or trap with this name");
+        }
+        if entry.weight < 0 {
+            error("weight can't be negative");
+        }
+        if entry.min_depth < 1 {
+            error("min_depth must be at least 1");
+        }
+        if entry.max_depth.is_some_and(|max| max < entry.min_depth) {
+            error("max_depth is shallower than min_depth");
+        }
+    }
+
+    errors
+}
+
+fn validate_resistances(
+    resistances: &[DamageType],
+    vulnerabilities: &[DamageType],
+) -> Result<(), String> {
+    match resistances
+        .iter()
+        .find(|kind| vulnerabilities.contains(kind))
+    {
+        Some(kind) => Err(format!(
+            "can't both resist and be vulnerable to {} damage",
+            kind
+        )),
+        None => Ok(()),
+    }
+}
+
+fn validate_renderable(renderable: &RenderableRaw) -> Result<(), String> {
+    if renderable.glyph.chars().count() != 1 {
+        return Err(format!(
+            "glyph '{}' must be a single character",
+            renderable.glyph
+        ));
+    }
+    if renderable.glyph() == 0 {
+        return Err(format!(
+            "glyph '{}' isn't in the game's font",
+            renderable.glyph
+        ));
+    }
+    let colours = std::iter::once(&renderable.foreground).chain(renderable.background.iter());
+    for colour in colours {
+        if RGB::from_hex(colour).is_err() {
+            return Err(format!("colour '{}' isn't a hex code like #FF0000", colour));
+        }
+    }
+    Ok(())
+}
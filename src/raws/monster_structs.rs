@@ -0,0 +1,51 @@
+use serde::Deserialize;
+
+use rltk::DiceType;
+
+use super::{parse_dice, RenderableRaw};
+use crate::components::{DamageType, DeathEffect, LootDrop};
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MonsterRaw {
+    pub name: String,
+    pub renderable: RenderableRaw,
+    pub blocks_tile: bool,
+    pub vision_range: i32,
+    pub stats: MonsterStatsRaw,
+    // Energy gained per tick, the player's is `ACTION_COST`
+    pub speed: i32,
+    // Earned by whoever lands the killing blow
+    pub xp: i32,
+    #[serde(default)]
+    pub resistances: Vec<DamageType>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<DamageType>,
+    #[serde(default)]
+    pub on_death: Vec<DeathEffect>,
+    #[serde(default)]
+    pub loot: Vec<LootDrop>,
+    // Items the monster spawns wearing, and fights with
+    #[serde(default)]
+    pub equipment: Vec<String>,
+}
+
+// What the monster has on the first level, deeper ones get a bonus on top
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MonsterStatsRaw {
+    pub max_hp: i32,
+    pub defense: i32,
+    pub power: i32,
+    #[serde(default)]
+    pub evasion: i32,
+    // Dice rolled for each melee hit, e.g. `1d6`
+    pub damage: String,
+    pub damage_type: DamageType,
+}
+
+impl MonsterStatsRaw {
+    pub fn damage(&self) -> DiceType {
+        parse_dice(&self.damage).unwrap_or_default()
+    }
+}
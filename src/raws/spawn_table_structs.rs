@@ -0,0 +1,28 @@
+use serde::Deserialize;
+
+// How likely a monster, item or trap is to turn up, and on which levels.
+// The weight grows by `depth_weight` on every level below the first
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SpawnTableEntry {
+    pub name: String,
+    pub weight: i32,
+    #[serde(default = "first_level")]
+    pub min_depth: i32,
+    pub max_depth: Option<i32>,
+    #[serde(default)]
+    pub depth_weight: i32,
+}
+
+fn first_level() -> i32 {
+    1
+}
+
+impl SpawnTableEntry {
+    pub fn weight_at(&self, depth: i32) -> i32 {
+        if depth < self.min_depth || self.max_depth.is_some_and(|max| depth > max) {
+            return 0;
+        }
+        self.weight + self.depth_weight * (depth - 1)
+    }
+}
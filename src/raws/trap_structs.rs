@@ -0,0 +1,19 @@
+use serde::Deserialize;
+
+use super::RenderableRaw;
+use crate::components::DamageType;
+
+// Traps are always hidden until spotted or set off, each effect present adds its component
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TrapRaw {
+    pub name: String,
+    pub renderable: RenderableRaw,
+    pub inflicts_damage: Option<i32>,
+    // For `inflicts_damage`, piercing otherwise
+    pub damage_type: Option<DamageType>,
+    #[serde(default)]
+    pub teleports: bool,
+    // How far away monsters hear the alarm
+    pub alarm: Option<i32>,
+}
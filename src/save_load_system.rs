@@ -0,0 +1,281 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, ErrorKind};
+use std::path::Path;
+
+use rltk::{Point, RandomNumberGenerator};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use specs::error::NoError;
+use specs::prelude::*;
+use specs::saveload::{
+    DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator,
+};
+
+use crate::components::*;
+use crate::game::{MonsterCounter, TurnCounter};
+use crate::map::Map;
+
+// Where the game saves unless `--save-file` says otherwise
+pub static SAVE_PATH: &str = "./savegame.json";
+
+// Bump whenever a component or the map changes shape, older saves are then refused
+// rather than loaded into a broken world
+const SAVE_VERSION: u32 = 13;
+
+macro_rules! serialize_individually {
+    ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
+        $(
+        SerializeComponents::<NoError, SimpleMarker<SerializeMe>>::serialize(
+            &( $ecs.read_storage::<$type>(), ),
+            &$data.0,
+            &$data.1,
+            &mut $ser,
+        )?;
+        )*
+    };
+}
+
+macro_rules! deserialize_individually {
+    ($ecs:expr, $de:expr, $data:expr, $( $type:ty),*) => {
+        $(
+        DeserializeComponents::<NoError, _>::deserialize(
+            &mut ( &mut $ecs.write_storage::<$type>(), ),
+            &$data.0,
+            &mut $data.1,
+            &mut $data.2,
+            &mut $de,
+        )?;
+        )*
+    };
+}
+
+pub fn does_save_exist(path: &str) -> bool {
+    Path::new(path).exists()
+}
+
+// Used for permadeath, a missing save isn't an error
+pub fn delete_save(path: &str) {
+    if does_save_exist(path) {
+        if let Err(error) = fs::remove_file(path) {
+            rltk::console::log(format!("Unable to delete the save: {}", error));
+        }
+    }
+}
+
+pub fn save_game(ecs: &mut World, seed: u64, path: &str) -> io::Result<()> {
+    let helper = {
+        let map = (*ecs.fetch::<Map>()).clone();
+        let turn = ecs.fetch::<TurnCounter>().0;
+        let monsters = ecs.fetch::<MonsterCounter>().0;
+        let rng = (*ecs.fetch::<RandomNumberGenerator>()).clone();
+        ecs.create_entity()
+            .with(SerializationHelper {
+                map,
+                turn,
+                monsters,
+                seed,
+                rng,
+            })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build()
+    };
+
+    let result = write_save(ecs, path);
+
+    ecs.delete_entity(helper)
+        .expect("Unable to delete serialization helper");
+    result
+}
+
+// Components that never outlive a turn, `SufferDamage`, `KilledBy` and `EntityMoved`, are left
+// out of both lists
+fn write_save(ecs: &World, path: &str) -> io::Result<()> {
+    let data = (
+        ecs.entities(),
+        ecs.read_storage::<SimpleMarker<SerializeMe>>(),
+    );
+
+    let writer = BufWriter::new(File::create(path)?);
+    let mut serializer = serde_json::Serializer::new(writer);
+    SAVE_VERSION.serialize(&mut serializer)?;
+    serialize_individually!(
+        ecs,
+        serializer,
+        data,
+        Position,
+        Renderable,
+        Player,
+        Viewshed,
+        Monster,
+        Name,
+        BlocksTile,
+        CombatStats,
+        WantsToMelee,
+        Item,
+        InBackpack,
+        WantsToPickupItem,
+        Consumable,
+        ProvidesHealing,
+        WantsToUseItem,
+        WantsToDropItem,
+        Ranged,
+        InflictsDamage,
+        AreaOfEffect,
+        Confused,
+        Poisoned,
+        Regenerating,
+        CausesConfusion,
+        CausesPoison,
+        ProvidesRegeneration,
+        Equippable,
+        Equipped,
+        MeleePowerBonus,
+        DefenseBonus,
+        WantsToRemoveItem,
+        Initiative,
+        MyTurn,
+        Experience,
+        GivesExperience,
+        CombatRecord,
+        MeleeDamageType,
+        Resistances,
+        Vulnerabilities,
+        OnDeath,
+        Corpse,
+        LootTable,
+        Door,
+        Hidden,
+        EntryTrigger,
+        Teleports,
+        Alarm,
+        Alerted,
+        SerializationHelper
+    );
+    Ok(())
+}
+
+// Replaces the whole world with the saved one, returning the seed that game was started from.
+// The world is only cleared once the file has been read and checked, anything wrong with what's
+// in it comes back as `InvalidData`
+pub fn load_game(ecs: &mut World, path: &str) -> io::Result<u64> {
+    let data = fs::read_to_string(path)?;
+    let invalid = |error: serde_json::Error| io::Error::new(ErrorKind::InvalidData, error);
+
+    let mut deserializer = serde_json::Deserializer::from_str(&data);
+    let version = u32::deserialize(&mut deserializer).map_err(invalid)?;
+    if version != SAVE_VERSION {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Save file is version {}, this build only reads version {}",
+                version, SAVE_VERSION
+            ),
+        ));
+    }
+    for value in serde_json::Deserializer::from_str(&data).into_iter::<IgnoredAny>() {
+        value.map_err(invalid)?;
+    }
+
+    ecs.delete_all();
+
+    {
+        let mut data = (
+            &mut ecs.entities(),
+            &mut ecs.write_storage::<SimpleMarker<SerializeMe>>(),
+            &mut ecs.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
+        );
+        deserialize_individually!(
+            ecs,
+            deserializer,
+            data,
+            Position,
+            Renderable,
+            Player,
+            Viewshed,
+            Monster,
+            Name,
+            BlocksTile,
+            CombatStats,
+            WantsToMelee,
+            Item,
+            InBackpack,
+            WantsToPickupItem,
+            Consumable,
+            ProvidesHealing,
+            WantsToUseItem,
+            WantsToDropItem,
+            Ranged,
+            InflictsDamage,
+            AreaOfEffect,
+            Confused,
+            Poisoned,
+            Regenerating,
+            CausesConfusion,
+            CausesPoison,
+            ProvidesRegeneration,
+            Equippable,
+            Equipped,
+            MeleePowerBonus,
+            DefenseBonus,
+            WantsToRemoveItem,
+            Initiative,
+            MyTurn,
+            Experience,
+            GivesExperience,
+            CombatRecord,
+            MeleeDamageType,
+            Resistances,
+            Vulnerabilities,
+            OnDeath,
+            Corpse,
+            LootTable,
+            Door,
+            Hidden,
+            EntryTrigger,
+            Teleports,
+            Alarm,
+            Alerted,
+            SerializationHelper
+        );
+    }
+
+    let mut helper_entity = None;
+    let mut seed = 0;
+    {
+        let entities = ecs.entities();
+        let helpers = ecs.read_storage::<SerializationHelper>();
+        for (entity, helper) in (&entities, &helpers).join() {
+            let mut map = helper.map.clone();
+            map.tile_content = vec![Vec::new(); (map.width * map.height) as usize];
+            *ecs.write_resource::<Map>() = map;
+            *ecs.write_resource::<TurnCounter>() = TurnCounter(helper.turn);
+            *ecs.write_resource::<MonsterCounter>() = MonsterCounter(helper.monsters);
+            *ecs.write_resource::<RandomNumberGenerator>() = helper.rng.clone();
+            seed = helper.seed;
+            helper_entity = Some(entity);
+        }
+    }
+    match helper_entity {
+        Some(helper) => ecs
+            .delete_entity(helper)
+            .expect("Unable to delete serialization helper"),
+        None => {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "Save file has no map in it",
+            ))
+        }
+    }
+
+    {
+        let entities = ecs.entities();
+        let players = ecs.read_storage::<Player>();
+        let positions = ecs.read_storage::<Position>();
+        for (entity, _player, position) in (&entities, &players, &positions).join() {
+            *ecs.write_resource::<Point>() = Point::new(position.x, position.y);
+            *ecs.write_resource::<Entity>() = entity;
+        }
+    }
+
+    Ok(seed)
+}
@@ -0,0 +1,377 @@
+use rltk::{DiceType, RandomNumberGenerator, RGB};
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+use crate::components::{
+    Alarm, AreaOfEffect, BlocksTile, CausesConfusion, CausesPoison, CombatStats, Consumable,
+    Corpse, DamageType, DefenseBonus, Door, EntryTrigger, EquipmentSlot, Equippable, Equipped,
+    Experience, GivesExperience, Hidden, InflictsDamage, Initiative, Item, LootTable,
+    MeleeDamageType, MeleePowerBonus, Monster, Name, OnDeath, Player, Position, ProvidesHealing,
+    ProvidesRegeneration, Ranged, Renderable, Resistances, SerializeMe, Teleports, Viewshed,
+    Vulnerabilities,
+};
+use crate::game::{MonsterCounter, PLAYER_VIEWSHED};
+use crate::initiative_system::ACTION_COST;
+use crate::map::{Map, TileType};
+use crate::random_table::RandomTable;
+use crate::raws::{ItemRaw, MonsterRaw, RawMaster, TrapRaw};
+
+pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position {
+            x: player_x,
+            y: player_y,
+        })
+        .with(Renderable {
+            glyph: rltk::to_cp437('@'),
+            foreground: RGB::named(rltk::YELLOW),
+            background: RGB::named(rltk::BLACK),
+            render_order: 0,
+        })
+        .with(Player {})
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: PLAYER_VIEWSHED,
+            dirty: true,
+        })
+        .with(Name {
+            name: "Player".to_string(),
+        })
+        .with(CombatStats {
+            max_hp: 30,
+            hp: 30,
+            defense: 2,
+            power: 3,
+            evasion: 2,
+            damage: DiceType::new(1, 6, 0),
+            damage_type: DamageType::Bludgeoning,
+        })
+        .with(Experience { level: 1, xp: 0 })
+        .with(Initiative {
+            speed: ACTION_COST,
+            energy: 0,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+// Up to this many spawns in a room on the first level, with one more for every second level below it
+const MAX_SPAWNS: i32 = 4;
+
+// The spawn table with each entry weighted for `depth`
+pub fn spawn_table(raws: &RawMaster, depth: i32) -> RandomTable {
+    raws.raws
+        .spawn_table
+        .iter()
+        .fold(RandomTable::new(), |table, entry| {
+            table.add(&entry.name, entry.weight_at(depth))
+        })
+}
+
+// Fills some of the map tiles in `area` from the spawn table, more of them the deeper it is.
+// Only empty floor is used and never more than one spawn per tile
+pub fn spawn_region(ecs: &mut World, map: &Map, area: &[usize]) {
+    let mut spawns: Vec<(usize, String)> = Vec::new();
+    {
+        let raws = ecs.fetch::<RawMaster>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let positions = ecs.read_storage::<Position>();
+        let occupied: Vec<usize> = positions
+            .join()
+            .map(|position| map.xy_index(position.x, position.y))
+            .collect();
+        let mut free: Vec<usize> = area
+            .iter()
+            .copied()
+            .filter(|index| map.tiles[*index] == TileType::Floor && !occupied.contains(index))
+            .collect();
+
+        let table = spawn_table(&raws, map.depth);
+        let count = rng.roll_dice(1, MAX_SPAWNS + 3) + (map.depth - 1) / 2 - 3;
+        for _ in 0..count {
+            if free.is_empty() {
+                break;
+            }
+            let chosen = rng.random_slice_index(&free).unwrap();
+            if let Some(name) = table.roll(&mut rng) {
+                spawns.push((free.remove(chosen), name.to_string()));
+            }
+        }
+    }
+
+    for (index, name) in spawns {
+        spawn_named(
+            ecs,
+            &name,
+            index as i32 % map.width,
+            index as i32 / map.width,
+            map.depth,
+        );
+    }
+}
+
+// Builds whichever monster, item or trap in the raws is called `name`
+pub fn spawn_named(ecs: &mut World, name: &str, x: i32, y: i32, depth: i32) -> Option<Entity> {
+    let (monster, item, trap) = {
+        let raws = ecs.fetch::<RawMaster>();
+        (
+            raws.monster(name).cloned(),
+            raws.item(name).cloned(),
+            raws.trap(name).cloned(),
+        )
+    };
+
+    if let Some(template) = monster {
+        // Numbered so monsters of the same kind can be told apart in messages
+        let number = {
+            let mut counter = ecs.fetch_mut::<MonsterCounter>();
+            counter.0 += 1;
+            counter.0
+        };
+        return Some(spawn_monster(ecs, &template, x, y, number, depth));
+    }
+    if let Some(template) = item {
+        return Some(spawn_item(ecs, &template, x, y));
+    }
+    trap.map(|template| spawn_trap(ecs, &template, x, y))
+}
+
+pub fn spawn_monster(
+    ecs: &mut World,
+    template: &MonsterRaw,
+    x: i32,
+    y: i32,
+    number: usize,
+    depth: i32,
+) -> Entity {
+    // Monsters get a little tougher on every level below the first
+    let bonus = depth - 1;
+    let max_hp = template.stats.max_hp + bonus * 2;
+
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: template.renderable.glyph(),
+            foreground: template.renderable.foreground(),
+            background: template.renderable.background(),
+            render_order: template.renderable.render_order,
+        })
+        .with(Viewshed {
+            visible_tiles: Vec::new(),
+            range: template.vision_range,
+            dirty: true,
+        })
+        .with(Monster {})
+        .with(Name {
+            name: format!("{} #{}", template.name, number),
+        })
+        .with(CombatStats {
+            max_hp,
+            hp: max_hp,
+            defense: template.stats.defense + bonus / 3,
+            power: template.stats.power + bonus / 2,
+            evasion: template.stats.evasion,
+            damage: template.stats.damage(),
+            damage_type: template.stats.damage_type,
+        })
+        .with(Initiative {
+            speed: template.speed,
+            energy: 0,
+        })
+        .with(GivesExperience {
+            xp: template.xp + bonus * 2,
+        });
+    if !template.resistances.is_empty() {
+        builder = builder.with(Resistances {
+            kinds: template.resistances.clone(),
+        });
+    }
+    if !template.on_death.is_empty() {
+        builder = builder.with(OnDeath {
+            effects: template.on_death.clone(),
+        });
+    }
+    if !template.loot.is_empty() {
+        builder = builder.with(LootTable {
+            drops: template.loot.clone(),
+        });
+    }
+    if !template.vulnerabilities.is_empty() {
+        builder = builder.with(Vulnerabilities {
+            kinds: template.vulnerabilities.clone(),
+        });
+    }
+    if template.blocks_tile {
+        builder = builder.with(BlocksTile {});
+    }
+    let monster = builder.marked::<SimpleMarker<SerializeMe>>().build();
+
+    for name in template.equipment.iter() {
+        let item = ecs.fetch::<RawMaster>().item(name).cloned();
+        if let Some(item) = item {
+            equip(ecs, &item, monster);
+        }
+    }
+    monster
+}
+
+// Puts a new item from `template` straight on `owner`, it only has a position once dropped
+pub fn equip(ecs: &mut World, template: &ItemRaw, owner: Entity) -> Option<Entity> {
+    let slot = template.equippable.as_ref()?.slot;
+    let item = spawn_item(ecs, template, 0, 0);
+    ecs.write_storage::<Position>().remove(item);
+    ecs.write_storage::<Equipped>()
+        .insert(item, Equipped { owner, slot })
+        .expect("Unable to equip item");
+    Some(item)
+}
+
+// Named after whatever died, e.g. `Goblin #2 corpse`
+pub fn corpse(ecs: &mut World, name: &str, x: i32, y: i32, foreground: RGB) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            foreground,
+            background: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name {
+            name: format!("{} corpse", name),
+        })
+        .with(Corpse {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+// Doors start out closed
+pub fn door(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let door = Door { open: false };
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: door.glyph(),
+            foreground: RGB::named(rltk::CHOCOLATE),
+            background: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name {
+            name: "Door".to_string(),
+        })
+        .with(door)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
+pub fn spawn_trap(ecs: &mut World, template: &TrapRaw, x: i32, y: i32) -> Entity {
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: template.renderable.glyph(),
+            foreground: template.renderable.foreground(),
+            background: template.renderable.background(),
+            render_order: template.renderable.render_order,
+        })
+        .with(Name {
+            name: template.name.clone(),
+        })
+        .with(Hidden {})
+        .with(EntryTrigger {});
+
+    if let Some(damage) = template.inflicts_damage {
+        builder = builder.with(InflictsDamage {
+            damage,
+            kind: template.damage_type.unwrap_or(DamageType::Piercing),
+        });
+    }
+    if template.teleports {
+        builder = builder.with(Teleports {});
+    }
+    if let Some(radius) = template.alarm {
+        builder = builder.with(Alarm { radius });
+    }
+
+    builder.marked::<SimpleMarker<SerializeMe>>().build()
+}
+
+pub fn spawn_item(ecs: &mut World, template: &ItemRaw, x: i32, y: i32) -> Entity {
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: template.renderable.glyph(),
+            foreground: template.renderable.foreground(),
+            background: template.renderable.background(),
+            render_order: template.renderable.render_order,
+        })
+        .with(Name {
+            name: template.name.clone(),
+        })
+        .with(Item {});
+
+    if template.consumable {
+        builder = builder.with(Consumable {});
+    }
+    if let Some(range) = template.ranged {
+        builder = builder.with(Ranged { range });
+    }
+    if let Some(damage) = template.inflicts_damage {
+        builder = builder.with(InflictsDamage {
+            damage,
+            kind: template.damage_type.unwrap_or(DamageType::Magic),
+        });
+    }
+    if let Some(radius) = template.area_of_effect {
+        builder = builder.with(AreaOfEffect { radius });
+    }
+    if let Some(heal_amount) = template.provides_healing {
+        builder = builder.with(ProvidesHealing { heal_amount });
+    }
+    if let Some(turns) = template.causes_confusion {
+        builder = builder.with(CausesConfusion { turns });
+    }
+    if let Some(poison) = &template.causes_poison {
+        builder = builder.with(CausesPoison {
+            turns: poison.turns,
+            damage: poison.damage,
+        });
+    }
+    if let Some(regeneration) = &template.provides_regeneration {
+        builder = builder.with(ProvidesRegeneration {
+            turns: regeneration.turns,
+            amount: regeneration.amount,
+        });
+    }
+    if let Some(equippable) = &template.equippable {
+        builder = builder.with(Equippable {
+            slot: equippable.slot,
+        });
+        if let (EquipmentSlot::Melee, Some(kind)) = (equippable.slot, template.damage_type) {
+            builder = builder.with(MeleeDamageType { kind });
+        }
+        if !equippable.resistances.is_empty() {
+            builder = builder.with(Resistances {
+                kinds: equippable.resistances.clone(),
+            });
+        }
+        if !equippable.vulnerabilities.is_empty() {
+            builder = builder.with(Vulnerabilities {
+                kinds: equippable.vulnerabilities.clone(),
+            });
+        }
+        if equippable.power_bonus != 0 {
+            builder = builder.with(MeleePowerBonus {
+                power: equippable.power_bonus,
+            });
+        }
+        if equippable.defense_bonus != 0 {
+            builder = builder.with(DefenseBonus {
+                defense: equippable.defense_bonus,
+            });
+        }
+    }
+
+    builder.marked::<SimpleMarker<SerializeMe>>().build()
+}
@@ -0,0 +1,91 @@
+use rltk::RGB;
+use specs::prelude::*;
+
+use crate::components::{
+    CombatStats, Confused, DamageType, Poisoned, Regenerating, StatusEffect, SufferDamage,
+};
+use crate::game::RunState;
+use crate::gamelog::GameLog;
+
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Confused>,
+        WriteStorage<'a, Poisoned>,
+        WriteStorage<'a, Regenerating>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            runstate,
+            mut log,
+            mut confused,
+            mut poisoned,
+            mut regenerating,
+            mut combat_stats,
+            mut suffer_damage,
+        ) = data;
+
+        // Effects tick once per player action, see `RunState::PlayerTurn`
+        if *runstate != RunState::PlayerTurn {
+            return;
+        }
+
+        for (entity, poison) in (&entities, &poisoned).join() {
+            SufferDamage::new_damage(
+                &mut suffer_damage,
+                entity,
+                poison.damage,
+                poison.source,
+                DamageType::Poison,
+            );
+            if entity == *player_entity {
+                log.push(
+                    format!("You take {} poison damage.", poison.damage),
+                    RGB::named(rltk::GREEN),
+                );
+            }
+        }
+
+        for (regeneration, stats) in (&regenerating, &mut combat_stats).join() {
+            stats.hp = i32::min(stats.max_hp, stats.hp + regeneration.amount);
+        }
+
+        tick_down(&entities, *player_entity, &mut log, &mut confused);
+        tick_down(&entities, *player_entity, &mut log, &mut poisoned);
+        tick_down(&entities, *player_entity, &mut log, &mut regenerating);
+    }
+}
+
+fn tick_down<T: Component + StatusEffect>(
+    entities: &Entities,
+    player_entity: Entity,
+    log: &mut GameLog,
+    effects: &mut WriteStorage<T>,
+) {
+    let mut expired = Vec::new();
+    for (entity, effect) in (entities, &mut *effects).join() {
+        *effect.turns() -= 1;
+        if *effect.turns() < 1 {
+            expired.push(entity);
+        }
+    }
+
+    for entity in expired {
+        if let Some(effect) = effects.remove(entity) {
+            if entity == player_entity {
+                log.log(format!("You are no longer {}.", effect.description()));
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use rltk::{DistanceAlg, Point, RandomNumberGenerator, RGB};
+use specs::prelude::*;
+
+use crate::components::{
+    Alarm, Alerted, EntityMoved, EntryTrigger, Hidden, InflictsDamage, Monster, Name, Position,
+    SufferDamage, Teleports, Viewshed,
+};
+use crate::game::RunState;
+use crate::gamelog::GameLog;
+use crate::map::{Map, TileType};
+
+// A d20 roll of at least this spots a hidden trap the player can see, rolled once a turn per trap
+const SPOT_ROLL: i32 = 17;
+
+// Sets off the traps under anything that moved, and gives the player a chance to spot the
+// hidden ones in view
+pub struct TrapSystem {}
+
+impl<'a> System<'a> for TrapSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, Point>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, EntityMoved>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Hidden>,
+        ReadStorage<'a, EntryTrigger>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, InflictsDamage>,
+        ReadStorage<'a, Teleports>,
+        ReadStorage<'a, Alarm>,
+        ReadStorage<'a, Monster>,
+        WriteStorage<'a, Alerted>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Viewshed>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            map,
+            player_entity,
+            mut player_position,
+            runstate,
+            mut log,
+            mut rng,
+            mut moved,
+            mut positions,
+            mut hidden,
+            triggers,
+            names,
+            inflicts_damage,
+            teleports,
+            alarms,
+            monsters,
+            mut alerted,
+            mut suffer_damage,
+            mut viewsheds,
+        ) = data;
+
+        let name_of = |entity: Entity| {
+            names
+                .get(entity)
+                .map_or("something", |name| name.name.as_str())
+                .to_string()
+        };
+
+        let movers: Vec<(Entity, usize)> = (&entities, &moved, &positions)
+            .join()
+            .map(|(entity, _, position)| (entity, map.xy_index(position.x, position.y)))
+            .collect();
+        moved.clear();
+
+        for (victim, index) in movers {
+            for trap in map.tile_content[index].iter().copied() {
+                if trap == victim || triggers.get(trap).is_none() {
+                    continue;
+                }
+                let trap_position = *positions.get(trap).unwrap();
+                hidden.remove(trap);
+                let message = match victim == *player_entity {
+                    true => format!("You set off the {}!", name_of(trap)),
+                    false => format!("{} sets off the {}!", name_of(victim), name_of(trap)),
+                };
+                log.push(message, RGB::named(rltk::ORANGE));
+
+                // Traps aren't attackers, so they don't get a combat record or the credit for a kill
+                if let Some(damage) = inflicts_damage.get(trap) {
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        victim,
+                        damage.damage,
+                        None,
+                        damage.kind,
+                    );
+                }
+
+                if let Some(alarm) = alarms.get(trap) {
+                    log.push("An alarm rings out!", RGB::named(rltk::YELLOW));
+                    let origin = Point::new(trap_position.x, trap_position.y);
+                    for (monster, _, position) in (&entities, &monsters, &positions).join() {
+                        let distance = DistanceAlg::Pythagoras
+                            .distance2d(origin, Point::new(position.x, position.y));
+                        if distance <= alarm.radius as f32 {
+                            alerted
+                                .insert(
+                                    monster,
+                                    Alerted {
+                                        x: trap_position.x,
+                                        y: trap_position.y,
+                                    },
+                                )
+                                .expect("Unable to alert monster");
+                        }
+                    }
+                }
+
+                if teleports.get(trap).is_some() {
+                    let free: Vec<usize> = (0..map.tiles.len())
+                        .filter(|index| {
+                            map.tiles[*index] == TileType::Floor && !map.blocked[*index]
+                        })
+                        .collect();
+                    if let Some(destination) = rng.random_slice_entry(&free) {
+                        let (x, y) = (
+                            *destination as i32 % map.width,
+                            *destination as i32 / map.width,
+                        );
+                        if let Some(position) = positions.get_mut(victim) {
+                            *position = Position { x, y };
+                        }
+                        if let Some(viewshed) = viewsheds.get_mut(victim) {
+                            viewshed.dirty = true;
+                        }
+                        if victim == *player_entity {
+                            *player_position = Point::new(x, y);
+                            log.log("You are teleported away!");
+                        } else {
+                            log.log(format!("{} vanishes!", name_of(victim)));
+                        }
+                    }
+                    // Whatever else is on this tile went off for someone who isn't there now
+                    break;
+                }
+            }
+        }
+
+        // Spotting is rolled once per player action, see `RunState::PlayerTurn`
+        if *runstate != RunState::PlayerTurn {
+            return;
+        }
+        let Some(viewshed) = viewsheds.get(*player_entity) else {
+            return;
+        };
+        for (trap, _, position) in (&entities, &triggers, &positions).join() {
+            let in_view = viewshed
+                .visible_tiles
+                .contains(&Point::new(position.x, position.y));
+            if in_view && hidden.get(trap).is_some() && rng.roll_dice(1, 20) >= SPOT_ROLL {
+                hidden.remove(trap);
+                log.push(
+                    format!("You spot a {}.", name_of(trap)),
+                    RGB::named(rltk::CYAN),
+                );
+            }
+        }
+    }
+}
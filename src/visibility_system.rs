@@ -33,7 +33,7 @@ impl<'life_time_a> System<'life_time_a> for VisibilitySystem {
                     pos.x >= 0 && pos.x < map.width && pos.y >= 0 && pos.y < map.height
                 });
 
-                player.get(entity).map(|_| {
+                if player.get(entity).is_some() {
                     for it in map.visible_tiles.iter_mut() {
                         *it = false
                     }
@@ -42,7 +42,7 @@ impl<'life_time_a> System<'life_time_a> for VisibilitySystem {
                         map.revealed_tiles[index] = true;
                         map.visible_tiles[index] = true;
                     }
-                });
+                }
             }
         }
     }
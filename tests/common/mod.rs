@@ -0,0 +1,379 @@
+// Test support for running the game's systems against small hand drawn maps, without a window
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rltk::{DiceType, Point};
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+use rust_game::components::{
+    BlocksTile, CombatRecord, CombatStats, DamageType, Door, EquipmentSlot, Equippable, Equipped,
+    Experience, Hidden, Initiative, Item, Monster, Name, Position, Renderable, SerializeMe,
+    Viewshed,
+};
+use rust_game::game::{RunState, State};
+use rust_game::gamelog::GameLog;
+use rust_game::headless::Headless;
+use rust_game::initiative_system::ACTION_COST;
+use rust_game::map::{Map, TileType};
+use rust_game::map_indexing_system::MapIndexingSystem;
+use rust_game::options::Options;
+use rust_game::raws::{CombatRaw, RawMaster};
+use rust_game::save_load_system;
+use rust_game::spawner;
+use rust_game::visibility_system::VisibilitySystem;
+
+// Every world saves to a file of its own in the temp directory, never the real `savegame.json`
+pub fn options() -> Options {
+    static WORLDS: AtomicUsize = AtomicUsize::new(0);
+    let save_file = std::env::temp_dir().join(format!(
+        "rust_game_test_{}_{}.json",
+        std::process::id(),
+        WORLDS.fetch_add(1, Ordering::Relaxed)
+    ));
+    Options {
+        seed: 1,
+        builder: None,
+        log_file: None,
+        save_file: save_file.to_string_lossy().into_owned(),
+        continue_game: false,
+        headless: true,
+    }
+}
+
+// A `Headless` game on a hand drawn map, so tests play it the way scripts do
+pub struct TestWorld {
+    pub game: Headless,
+}
+
+impl TestWorld {
+    // `#` is a wall, `.` floor, `>` the way down, `+` a closed door on floor and `@` the player
+    // standing on floor. Leading and trailing whitespace on each row is ignored
+    pub fn from_ascii(ascii: &str) -> TestWorld {
+        let mut state = State::new(options()).expect("Unable to create the world");
+        state.ecs.delete_all();
+        state.ecs.maintain();
+
+        let rows: Vec<&str> = ascii
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0) as i32;
+        let height = rows.len() as i32;
+        let size = (width * height) as usize;
+
+        let mut map = Map::new(1);
+        map.width = width;
+        map.height = height;
+        map.tiles = vec![TileType::Wall; size];
+        map.revealed_tiles = vec![false; size];
+        map.visible_tiles = vec![false; size];
+        map.blocked = vec![false; size];
+        map.closed_doors = vec![false; size];
+        map.tile_content = vec![Vec::new(); size];
+
+        let mut player_start = None;
+        let mut doors = Vec::new();
+        for (y, row) in (0..).zip(rows.iter()) {
+            for (x, glyph) in (0..).zip(row.chars()) {
+                let index = map.xy_index(x, y);
+                map.tiles[index] = match glyph {
+                    '#' => TileType::Wall,
+                    '.' => TileType::Floor,
+                    '>' => TileType::DownStairs,
+                    '+' => {
+                        doors.push((x, y));
+                        TileType::Floor
+                    }
+                    '@' => {
+                        player_start = Some(Point::new(x, y));
+                        TileType::Floor
+                    }
+                    _ => panic!("Unknown map glyph '{}'", glyph),
+                };
+            }
+        }
+        map.populate_blocked();
+        state.ecs.insert(map);
+
+        let start = player_start.expect("The map has no '@' for the player");
+        let player = spawner::player(&mut state.ecs, start.x, start.y);
+        state.ecs.insert(player);
+        state.ecs.insert(start);
+        state.ecs.insert(RunState::AwaitingInput);
+        for (x, y) in doors {
+            spawner::door(&mut state.ecs, x, y);
+        }
+
+        let mut world = TestWorld {
+            game: Headless { state },
+        };
+        world.set_combat_rules(sure_hits());
+        world.insert(player, stats(30, 2, 5));
+        world.index_map();
+        world
+    }
+
+    pub fn ecs(&self) -> &World {
+        &self.game.state.ecs
+    }
+
+    pub fn player(&self) -> Entity {
+        *self.game.state.ecs.fetch::<Entity>()
+    }
+
+    // Gives `entity` the component, replacing any of the same type it already has
+    pub fn insert<C: Component>(&mut self, entity: Entity, component: C) {
+        self.game
+            .state
+            .ecs
+            .write_storage::<C>()
+            .insert(entity, component)
+            .expect("Unable to insert component");
+    }
+
+    pub fn spawn_monster(&mut self, name: &str, x: i32, y: i32, stats: CombatStats) -> Entity {
+        let monster = self
+            .game
+            .state
+            .ecs
+            .create_entity()
+            .with(Position { x, y })
+            .with(Renderable {
+                glyph: rltk::to_cp437('m'),
+                foreground: rltk::RGB::named(rltk::RED),
+                background: rltk::RGB::named(rltk::BLACK),
+                render_order: 1,
+            })
+            .with(Viewshed {
+                visible_tiles: Vec::new(),
+                range: 8,
+                dirty: true,
+            })
+            .with(Monster {})
+            .with(Name {
+                name: name.to_string(),
+            })
+            .with(BlocksTile {})
+            .with(stats)
+            .with(Initiative {
+                speed: ACTION_COST,
+                energy: 0,
+            })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+        self.index_map();
+        monster
+    }
+
+    // Anything in the raws, built the way the map builders do it
+    pub fn spawn_named(&mut self, name: &str, x: i32, y: i32) -> Entity {
+        let entity = spawner::spawn_named(&mut self.game.state.ecs, name, x, y, 1)
+            .expect("There's nothing in the raws with that name");
+        self.index_map();
+        entity
+    }
+
+    // The item called `name` in the raws, worn by `owner`
+    pub fn equip_named(&mut self, owner: Entity, name: &str) -> Entity {
+        let template = self
+            .game
+            .state
+            .ecs
+            .fetch::<RawMaster>()
+            .item(name)
+            .cloned()
+            .expect("No such item");
+        spawner::equip(&mut self.game.state.ecs, &template, owner).expect("Item isn't equippable")
+    }
+
+    // A bare item worn by `owner` in `slot`, for tests to give components to
+    pub fn equip(&mut self, owner: Entity, slot: EquipmentSlot) -> Entity {
+        self.game
+            .state
+            .ecs
+            .create_entity()
+            .with(Item {})
+            .with(Equippable { slot })
+            .with(Equipped { owner, slot })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build()
+    }
+
+    pub fn set_combat_rules(&mut self, rules: CombatRaw) {
+        self.game
+            .state
+            .ecs
+            .write_resource::<RawMaster>()
+            .raws
+            .combat = rules;
+    }
+
+    pub fn position(&self, entity: Entity) -> (i32, i32) {
+        let positions = self.game.state.ecs.read_storage::<Position>();
+        let position = positions.get(entity).expect("Entity has no position");
+        (position.x, position.y)
+    }
+
+    pub fn hp(&self, entity: Entity) -> i32 {
+        let stats = self.game.state.ecs.read_storage::<CombatStats>();
+        stats.get(entity).expect("Entity has no stats").hp
+    }
+
+    pub fn stats(&self, entity: Entity) -> CombatStats {
+        let stats = self.game.state.ecs.read_storage::<CombatStats>();
+        stats.get(entity).expect("Entity has no stats").clone()
+    }
+
+    pub fn experience(&self, entity: Entity) -> Experience {
+        let experience = self.game.state.ecs.read_storage::<Experience>();
+        experience
+            .get(entity)
+            .expect("Entity has no experience")
+            .clone()
+    }
+
+    // Records are only added once there's something to record
+    pub fn combat_record(&self, entity: Entity) -> CombatRecord {
+        let records = self.game.state.ecs.read_storage::<CombatRecord>();
+        records.get(entity).cloned().unwrap_or_default()
+    }
+
+    pub fn save(&mut self) {
+        let seed = self.game.state.options.seed;
+        save_load_system::save_game(
+            &mut self.game.state.ecs,
+            seed,
+            &self.game.state.options.save_file,
+        )
+        .expect("Unable to save the game");
+    }
+
+    // Replaces the world with the saved one and runs it up to the player's next turn
+    pub fn load(&mut self) {
+        self.game
+            .state
+            .continue_game()
+            .expect("Unable to load the game");
+        self.game.settle();
+    }
+
+    pub fn has_save(&self) -> bool {
+        save_load_system::does_save_exist(&self.game.state.options.save_file)
+    }
+
+    pub fn logged(&self, text: &str) -> bool {
+        self.times_logged(text) > 0
+    }
+
+    pub fn times_logged(&self, text: &str) -> usize {
+        let log = self.game.state.ecs.fetch::<GameLog>();
+        log.entries
+            .iter()
+            .filter(|entry| entry.text == text)
+            .count()
+    }
+
+    // The names of everything at `x`, `y`, living or not
+    pub fn names_at(&self, x: i32, y: i32) -> Vec<String> {
+        let positions = self.game.state.ecs.read_storage::<Position>();
+        let names = self.game.state.ecs.read_storage::<Name>();
+        (&positions, &names)
+            .join()
+            .filter(|(position, _)| position.x == x && position.y == y)
+            .map(|(_, name)| name.name.clone())
+            .collect()
+    }
+
+    // How many entities have a `C`
+    pub fn count<C: Component>(&self) -> usize {
+        self.game.state.ecs.read_storage::<C>().join().count()
+    }
+
+    pub fn monster_named(&self, name: &str) -> Entity {
+        let entities = self.game.state.ecs.entities();
+        let monsters = self.game.state.ecs.read_storage::<Monster>();
+        let names = self.game.state.ecs.read_storage::<Name>();
+        (&entities, &monsters, &names)
+            .join()
+            .find(|(_, _, monster_name)| monster_name.name == name)
+            .map(|(entity, _, _)| entity)
+            .expect("There's no monster with that name")
+    }
+
+    pub fn monster_names(&self) -> Vec<String> {
+        let monsters = self.game.state.ecs.read_storage::<Monster>();
+        let names = self.game.state.ecs.read_storage::<Name>();
+        (&monsters, &names)
+            .join()
+            .map(|(_, name)| name.name.clone())
+            .collect()
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.game.state.ecs.is_alive(entity)
+    }
+
+    pub fn is_visible(&self, x: i32, y: i32) -> bool {
+        let map = self.game.state.ecs.fetch::<Map>();
+        map.visible_tiles[map.xy_index(x, y)]
+    }
+
+    pub fn is_hidden(&self, entity: Entity) -> bool {
+        self.game
+            .state
+            .ecs
+            .read_storage::<Hidden>()
+            .get(entity)
+            .is_some()
+    }
+
+    pub fn is_door_open(&self, x: i32, y: i32) -> bool {
+        let positions = self.game.state.ecs.read_storage::<Position>();
+        let doors = self.game.state.ecs.read_storage::<Door>();
+        (&positions, &doors)
+            .join()
+            .find(|(position, _)| position.x == x && position.y == y)
+            .map(|(_, door)| door.open)
+            .expect("There's no door there")
+    }
+
+    fn index_map(&mut self) {
+        MapIndexingSystem {}.run_now(&self.game.state.ecs);
+        VisibilitySystem {}.run_now(&self.game.state.ecs);
+    }
+}
+
+impl Drop for TestWorld {
+    fn drop(&mut self) {
+        save_load_system::delete_save(&self.game.state.options.save_file);
+    }
+}
+
+// No damage dice or evasion, so with `sure_hits` melee always hits for power minus defense
+pub fn stats(hp: i32, defense: i32, power: i32) -> CombatStats {
+    CombatStats {
+        max_hp: hp,
+        hp,
+        defense,
+        power,
+        evasion: 0,
+        damage: DiceType::new(0, 0, 0),
+        damage_type: DamageType::Bludgeoning,
+    }
+}
+
+// Every attack lands, without any critical hits or fumbles
+pub fn sure_hits() -> CombatRaw {
+    CombatRaw {
+        hit_target: 1,
+        crit_roll: 21,
+        crit_multiplier: 2.0,
+        fumble_roll: 0,
+        fumble_multiplier: 0.5,
+        resistance_multiplier: 0.5,
+        vulnerability_multiplier: 2.0,
+    }
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use std::io::Cursor;
+
+use common::{options, stats, TestWorld};
+use rust_game::components::CombatStats;
+use rust_game::game::RunState;
+use rust_game::headless::run_script;
+
+const ROOM: &str = "
+    ##########
+    #@.......#
+    #........#
+    #........#
+    ##########
+";
+
+#[test]
+fn a_script_plays_to_a_known_end() {
+    let mut world = TestWorld::from_ascii(ROOM);
+
+    for line in ["e", "se", "wait 3", "n", "w"] {
+        assert_eq!(world.game.play_line(line), Ok(RunState::AwaitingInput));
+    }
+
+    assert_eq!(
+        world.game.summary(),
+        "Turn 7, depth 1, HP 30 / 30 at (2, 1)"
+    );
+}
+
+#[test]
+fn bad_lines_are_refused_without_taking_a_turn() {
+    let mut world = TestWorld::from_ascii(ROOM);
+
+    let errors = [
+        ("jump", "Unknown command 'jump'"),
+        ("wait soon", "Invalid number 'soon'"),
+        ("use", "Missing the item's letter"),
+        ("use 1", "Invalid item letter '1'"),
+        ("drop a", "No item with the letter 'a'"),
+        ("remove a", "No item with the letter 'a'"),
+    ];
+    for (line, error) in errors {
+        assert_eq!(world.game.play_line(line), Err(error.to_string()));
+    }
+
+    assert_eq!(
+        world.game.summary(),
+        "Turn 0, depth 1, HP 30 / 30 at (1, 1)"
+    );
+}
+
+#[test]
+fn items_are_picked_by_their_menu_letter() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(
+        player,
+        CombatStats {
+            hp: 10,
+            ..stats(30, 2, 5)
+        },
+    );
+    world.spawn_named("Health Potion", 1, 1);
+    world.spawn_named("Magic Missile Scroll", 2, 1);
+
+    world.game.play_line("pickup").unwrap();
+    world.game.play_line("use a").unwrap();
+    assert!(world.hp(player) > 10);
+
+    world.game.play_line("e").unwrap();
+    world.game.play_line("pickup").unwrap();
+    assert_eq!(
+        world.game.play_line("use a"),
+        Err("that item needs a target".to_string())
+    );
+    assert_eq!(
+        world.game.play_line("use a 5"),
+        Err("use needs both an x and a y to target".to_string())
+    );
+    world.game.play_line("drop a").unwrap();
+    assert!(world
+        .names_at(2, 1)
+        .contains(&"Magic Missile Scroll".to_string()));
+}
+
+#[test]
+fn scripts_skip_comments_and_stop_at_the_first_bad_line() {
+    let script = "# Look around\n\nwait 2\n";
+    assert!(run_script(options(), Cursor::new(script)).is_ok());
+
+    let script = "wait\njump\nwait\n";
+    let error = run_script(options(), Cursor::new(script)).unwrap_err();
+    assert_eq!(error.to_string(), "Line 2: Unknown command 'jump'");
+}
@@ -0,0 +1,135 @@
+use rltk::RandomNumberGenerator;
+use rust_game::raws::RawMaster;
+use rust_game::spawner::spawn_table;
+
+#[test]
+fn built_in_raws_are_valid() {
+    let raws = RawMaster::load().unwrap();
+    assert!(raws.monster("Orc").is_some());
+    assert!(raws.item("Health Potion").is_some());
+    assert!(raws.trap("Bear Trap").is_some());
+}
+
+#[test]
+fn errors_name_every_bad_entry() {
+    let json = r##"{
+        "combat": {
+            "hit_target": 6,
+            "crit_roll": 20,
+            "crit_multiplier": 2.0,
+            "fumble_roll": 20,
+            "fumble_multiplier": 0.5,
+            "resistance_multiplier": 0.5,
+            "vulnerability_multiplier": 0.5
+        },
+        "monsters": [
+            {
+                "name": "Slug",
+                "renderable": { "glyph": "sl", "foreground": "#00FF00", "render_order": 1 },
+                "blocks_tile": true,
+                "vision_range": 4,
+                "stats": { "max_hp": 4, "defense": 0, "power": -1, "damage": "d6", "damage_type": "Slashing" },
+                "resistances": ["Fire"],
+                "vulnerabilities": ["Fire"],
+                "on_death": [{ "Split": { "into": "Snail", "count": 2 } }],
+                "loot": [{ "item": "Gold", "chance": 50 }],
+                "equipment": ["Slug"],
+                "speed": 0,
+                "xp": -1
+            }
+        ],
+        "items": [
+            {
+                "name": "Slug",
+                "renderable": { "glyph": "~", "foreground": "green", "render_order": 2 },
+                "inflicts_damage": 3
+            },
+            {
+                "name": "Dud Scroll",
+                "renderable": { "glyph": "?", "foreground": "#FFFFFF", "render_order": 2 },
+                "consumable": true,
+                "ranged": 0,
+                "area_of_effect": 0,
+                "provides_healing": 0,
+                "causes_poison": { "turns": 0, "damage": 1 }
+            }
+        ],
+        "traps": [
+            {
+                "name": "Pit",
+                "renderable": { "glyph": "^", "foreground": "#888888", "render_order": 3 },
+                "damage_type": "Bludgeoning"
+            }
+        ],
+        "spawn_table": [
+            { "name": "Snail", "weight": 1, "min_depth": 3, "max_depth": 2 }
+        ]
+    }"##;
+
+    let error = RawMaster::from_json(json).err().unwrap();
+    assert!(error.contains("combat: fumble_roll must be below crit_roll"));
+    assert!(error.contains("combat: vulnerability_multiplier must be at least 1"));
+    assert!(error.contains(r#"monsters[0] "Slug": damage 'd6' isn't dice"#));
+    assert!(error.contains(r#"monsters[0] "Slug": on_death: there's no monster to split into"#));
+    assert!(error.contains(r#"monsters[0] "Slug": loot: there's no item called 'Gold'"#));
+    assert!(error.contains(r#"monsters[0] "Slug": equipment: 'Slug' isn't an equippable item"#));
+    assert!(error.contains(r#"monsters[0] "Slug": can't both resist and be vulnerable to fire"#));
+    assert!(error.contains(r#"monsters[0] "Slug": glyph 'sl' must be a single character"#));
+    assert!(error.contains(r#"monsters[0] "Slug": speed must be at least 1"#));
+    assert!(error.contains(r#"monsters[0] "Slug": xp can't be negative"#));
+    assert!(error.contains(r#"items[0] "Slug": the name is already taken"#));
+    assert!(error.contains(r#"items[0] "Slug": colour 'green'"#));
+    assert!(error.contains(r#"items[0] "Slug": damage, confusion and poison need a ranged item"#));
+    assert!(error.contains(r#"monsters[0] "Slug": power, defense and evasion can't be negative"#));
+    assert!(error.contains(r#"items[1] "Dud Scroll": ranged must be a range of at least 1"#));
+    assert!(
+        error.contains(r#"items[1] "Dud Scroll": area_of_effect must be a radius of at least 1"#)
+    );
+    assert!(error.contains(r#"items[1] "Dud Scroll": provides_healing must be at least 1"#));
+    assert!(
+        error.contains(r#"items[1] "Dud Scroll": confusion, poison and regeneration must last"#)
+    );
+    assert!(error.contains(r#"spawn_table[0] "Snail": there's no monster, item or trap"#));
+    assert!(error.contains(r#"traps[0] "Pit": a trap needs inflicts_damage, teleports or alarm"#));
+    assert!(error.contains(r#"traps[0] "Pit": damage_type needs inflicts_damage"#));
+    assert!(error.contains(r#"spawn_table[0] "Snail": max_depth is shallower than min_depth"#));
+}
+
+#[test]
+fn unknown_fields_are_rejected_with_their_line() {
+    let json = r##"{
+        "monsters": [],
+        "items": [
+            {
+                "name": "Rock",
+                "renderable": { "glyph": "*", "foreground": "#888888", "render_order": 2 },
+                "weight": 3
+            }
+        ],
+        "spawn_table": []
+    }"##;
+
+    let error = RawMaster::from_json(json).err().unwrap();
+    assert!(error.contains("unknown field `weight`"), "{}", error);
+    assert!(error.contains("line 7"), "{}", error);
+}
+
+#[test]
+fn spawn_table_follows_depth() {
+    let raws = RawMaster::load().unwrap();
+    let mut rng = RandomNumberGenerator::seeded(1);
+
+    let first_level = spawn_table(&raws, 1);
+    let rolls: Vec<String> = (0..500)
+        .map(|_| first_level.roll(&mut rng).unwrap().to_string())
+        .collect();
+    assert!(rolls.iter().any(|name| name == "Goblin"));
+    assert!(!rolls.iter().any(|name| name == "Zombie"));
+
+    let deep_level = spawn_table(&raws, 10);
+    let rolls: Vec<String> = (0..500)
+        .map(|_| deep_level.roll(&mut rng).unwrap().to_string())
+        .collect();
+    assert!(rolls.iter().any(|name| name == "Zombie"));
+    assert!(!rolls.iter().any(|name| name == "Bat" || name == "Dagger"));
+}
@@ -0,0 +1,793 @@
+mod common;
+
+use common::{stats, sure_hits, TestWorld};
+use rltk::DiceType;
+use rust_game::components::{
+    CombatRecord, Corpse, DamageType, DeathEffect, EquipmentSlot, GivesExperience, InBackpack,
+    Initiative, LootDrop, LootTable, OnDeath, Poisoned, Resistances, Vulnerabilities,
+};
+use rust_game::game::RunState;
+use rust_game::player::Command;
+use rust_game::raws::{CombatRaw, RawMaster};
+
+const ROOM: &str = "
+    ##########
+    #@.......#
+    #........#
+    #........#
+    ##########
+";
+
+#[test]
+fn monster_walks_up_to_a_visible_player() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let orc = world.spawn_monster("Orc", 8, 1, stats(10, 0, 0));
+
+    world.game.advance(10);
+
+    let (x, y) = world.position(orc);
+    assert!(x <= 2 && y <= 2, "orc stopped at ({}, {})", x, y);
+    assert_eq!(world.hp(world.player()), 30);
+}
+
+#[test]
+fn monster_hits_for_power_minus_defense() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(30, 2, 5));
+    world.spawn_monster("Orc", 2, 1, stats(10, 0, 6));
+
+    world.game.advance(3);
+
+    assert_eq!(world.hp(player), 30 - 3 * 4);
+}
+
+#[test]
+fn critical_hits_multiply_the_damage_roll() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    world.set_combat_rules(CombatRaw {
+        crit_roll: 1,
+        ..sure_hits()
+    });
+    let goblin = world.spawn_monster("Goblin", 2, 1, stats(20, 1, 0));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+
+    assert_eq!(world.hp(goblin), 20 - (5 * 2 - 1));
+    assert!(world.logged("Player critically hits Goblin, for 9 hp!"));
+}
+
+#[test]
+fn fumbles_hurt_the_attacker_instead() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    world.set_combat_rules(CombatRaw {
+        fumble_roll: 20,
+        fumble_multiplier: 0.5,
+        ..sure_hits()
+    });
+    let player = world.player();
+    let goblin = world.spawn_monster("Goblin", 2, 1, stats(20, 1, 0));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+
+    assert_eq!(world.hp(goblin), 20);
+    assert_eq!(world.hp(player), 30 - 2);
+    assert!(world.logged("You fumble and hurt yourself, for 2 hp."));
+    assert_eq!(world.combat_record(player).damage_dealt, 0);
+}
+
+#[test]
+fn evasive_monsters_are_missed() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let mut goblin_stats = stats(20, 0, 0);
+    goblin_stats.evasion = 20;
+    let goblin = world.spawn_monster("Goblin", 2, 1, goblin_stats);
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+
+    assert_eq!(world.hp(goblin), 20);
+    assert!(world.logged("Player misses Goblin."));
+}
+
+#[test]
+fn the_same_seed_fights_the_same_way() {
+    let fight = || {
+        let mut world = TestWorld::from_ascii(ROOM);
+        let raws = RawMaster::load().unwrap();
+        world.set_combat_rules(raws.raws.combat);
+        let player = world.player();
+        let mut player_stats = stats(30, 2, 0);
+        player_stats.damage = DiceType::new(1, 6, 0);
+        world.insert(player, player_stats);
+        let goblin = world.spawn_monster("Goblin", 2, 1, stats(500, 0, 0));
+
+        (0..20)
+            .map(|_| {
+                world.game.step(Command::Move {
+                    delta_x: 1,
+                    delta_y: 0,
+                });
+                world.hp(goblin)
+            })
+            .collect::<Vec<i32>>()
+    };
+
+    let hp = fight();
+    assert_eq!(hp, fight());
+    assert!(
+        hp.windows(2).any(|pair| pair[0] == pair[1]),
+        "nothing missed"
+    );
+    assert!(hp.windows(2).any(|pair| pair[0] != pair[1]), "nothing hit");
+}
+
+#[test]
+fn resistances_and_vulnerabilities_scale_damage() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(30, 0, 6));
+    let zombie = world.spawn_monster("Zombie", 2, 1, stats(50, 0, 0));
+    let bat = world.spawn_monster("Bat", 1, 2, stats(50, 0, 0));
+    world.insert(
+        zombie,
+        Resistances {
+            kinds: vec![DamageType::Bludgeoning],
+        },
+    );
+    world.insert(
+        bat,
+        Vulnerabilities {
+            kinds: vec![DamageType::Bludgeoning],
+        },
+    );
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    world.game.step(Command::Move {
+        delta_x: 0,
+        delta_y: 1,
+    });
+
+    assert_eq!(world.hp(zombie), 50 - 3);
+    assert_eq!(world.hp(bat), 50 - 12);
+    assert!(world.logged("Zombie resists the bludgeoning damage, taking 3 hp."));
+}
+
+#[test]
+fn equipped_armour_resists_damage() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    let shield = world.equip(player, EquipmentSlot::Shield);
+    world.insert(
+        shield,
+        Resistances {
+            kinds: vec![DamageType::Piercing],
+        },
+    );
+    let mut goblin_stats = stats(10, 0, 6);
+    goblin_stats.damage_type = DamageType::Piercing;
+    world.spawn_monster("Goblin", 2, 1, goblin_stats);
+
+    world.game.advance(2);
+
+    // Power 6 less the player's 2 defense, then halved
+    assert_eq!(world.hp(player), 30 - 2 * 2);
+}
+
+#[test]
+fn player_kills_monster_by_moving_into_it() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let goblin = world.spawn_monster("Goblin", 2, 1, stats(8, 1, 0));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    assert_eq!(world.hp(goblin), 4);
+    assert_eq!(world.position(world.player()), (1, 1));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    assert!(!world.is_alive(goblin));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    assert_eq!(world.position(world.player()), (2, 1));
+}
+
+#[test]
+fn killing_blow_earns_experience_and_levels_up() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(30, 2, 5));
+    let goblin = world.spawn_monster("Goblin", 2, 1, stats(1, 0, 0));
+    world.insert(goblin, GivesExperience { xp: 60 });
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    assert!(!world.is_alive(goblin));
+
+    let experience = world.experience(player);
+    assert_eq!((experience.level, experience.xp), (2, 10));
+    let stats = world.stats(player);
+    assert_eq!((stats.hp, stats.max_hp, stats.power), (35, 35, 6));
+}
+
+#[test]
+fn a_killer_that_dies_in_the_same_turn_does_not_level_up() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(1, 2, 5));
+    world.insert(
+        player,
+        Poisoned {
+            turns: 5,
+            damage: 5,
+            source: None,
+        },
+    );
+    let goblin = world.spawn_monster("Goblin", 2, 1, stats(1, 0, 0));
+    world.insert(goblin, GivesExperience { xp: 60 });
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+
+    assert!(!world.is_alive(goblin));
+    assert_eq!(world.game.state.run_state(), RunState::GameOver);
+    assert_eq!(world.experience(player).level, 1);
+    assert!(world.hp(player) < 1);
+}
+
+#[test]
+fn poison_kills_are_credited_to_the_poisoner() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    let goblin = world.spawn_monster("Goblin", 7, 2, stats(2, 0, 0));
+    world.insert(goblin, GivesExperience { xp: 60 });
+    let scroll = world.spawn_named("Poison Cloud Scroll", 5, 3);
+    world.insert(scroll, InBackpack { owner: player });
+
+    world.game.play_line("use a 7 2").unwrap();
+    world.game.play_line("wait").unwrap();
+
+    assert!(!world.is_alive(goblin));
+    assert!(world.logged("You killed Goblin."));
+    assert_eq!(world.combat_record(player).kills, 1);
+    assert_eq!(world.experience(player).level, 2);
+}
+
+#[test]
+fn dead_monsters_leave_a_corpse_to_walk_over() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    world.spawn_monster("Goblin", 2, 1, stats(1, 0, 0));
+
+    for _ in 0..2 {
+        world.game.step(Command::Move {
+            delta_x: 1,
+            delta_y: 0,
+        });
+    }
+
+    assert_eq!(world.position(world.player()), (2, 1));
+    assert!(world.names_at(2, 1).contains(&"Goblin corpse".to_string()));
+}
+
+#[test]
+fn monsters_killed_by_a_scroll_do_not_hit_back() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    let goblin = world.spawn_monster("Goblin", 2, 1, stats(5, 0, 8));
+    let scroll = world.spawn_named("Magic Missile Scroll", 5, 3);
+    world.insert(scroll, InBackpack { owner: player });
+
+    world.game.play_line("use a 2 1").unwrap();
+
+    assert!(!world.is_alive(goblin));
+    assert_eq!(world.hp(player), 30);
+}
+
+#[test]
+fn explosions_hurt_everything_nearby() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    let beetle = world.spawn_monster("Beetle", 2, 1, stats(1, 0, 0));
+    world.insert(
+        beetle,
+        OnDeath {
+            effects: vec![DeathEffect::Explode {
+                radius: 1,
+                damage: 8,
+                kind: DamageType::Fire,
+            }],
+        },
+    );
+    let goblin = world.spawn_monster("Goblin", 3, 1, stats(5, 0, 0));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+
+    assert_eq!(world.hp(player), 30 - 8);
+    assert!(!world.is_alive(goblin));
+    assert!(world.logged("Beetle explodes!"));
+    assert!(world.logged("Goblin was killed by Beetle."));
+}
+
+#[test]
+fn splitting_monsters_leave_smaller_ones_behind() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let jelly = world.spawn_monster("Jelly", 2, 1, stats(1, 0, 0));
+    world.insert(
+        jelly,
+        OnDeath {
+            effects: vec![DeathEffect::Split {
+                into: "Jelly Blob".to_string(),
+                count: 2,
+            }],
+        },
+    );
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+
+    let blobs = world
+        .monster_names()
+        .iter()
+        .filter(|name| name.starts_with("Jelly Blob"))
+        .count();
+    assert_eq!(blobs, 2);
+}
+
+#[test]
+fn dead_monsters_drop_their_loot_and_equipment() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let orc = world.spawn_monster("Orc", 2, 1, stats(1, 0, 0));
+    world.equip_named(orc, "Dagger");
+    world.insert(
+        orc,
+        LootTable {
+            drops: vec![LootDrop {
+                item: "Health Potion".to_string(),
+                chance: 100,
+                quantity: 2,
+            }],
+        },
+    );
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+
+    let names = world.names_at(2, 1);
+    let potions = names.iter().filter(|name| *name == "Health Potion").count();
+    assert_eq!(potions, 2);
+    assert!(names.contains(&"Dagger".to_string()));
+}
+
+#[test]
+fn monster_numbers_are_never_reused() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let first = world.spawn_named("Goblin", 2, 1);
+    world.insert(first, stats(1, 0, 0));
+    world.spawn_named("Goblin", 8, 3);
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    assert!(!world.is_alive(first));
+
+    world.spawn_named("Goblin", 8, 2);
+
+    let names = world.monster_names();
+    assert_eq!(names.len(), 2);
+    assert_ne!(names[0], names[1]);
+}
+
+#[test]
+fn monsters_fight_with_their_equipment() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    let orc = world.spawn_monster("Orc", 2, 1, stats(10, 0, 3));
+    world.equip_named(orc, "Dagger");
+
+    world.game.advance(2);
+
+    // The dagger's 2 power on top of the orc's 3, less the player's 2 defense
+    assert_eq!(world.hp(player), 30 - 2 * 3);
+}
+
+#[test]
+fn player_death_ends_the_game() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(5, 0, 0));
+    world.spawn_monster("Orc", 2, 2, stats(10, 0, 5));
+    world.save();
+
+    world.game.advance(5);
+
+    assert_eq!(world.game.state.run_state(), RunState::GameOver);
+    assert!(world.is_alive(player));
+    assert!(world.hp(player) < 1);
+    assert_eq!(world.times_logged("You were killed by Orc."), 1);
+    assert!(!world.has_save());
+}
+
+#[test]
+fn the_game_over_screen_quits_to_the_main_menu() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(5, 0, 0));
+    world.spawn_monster("Orc", 2, 2, stats(10, 0, 5));
+    world.game.advance(5);
+    assert_eq!(world.game.state.run_state(), RunState::GameOver);
+
+    let next = world.game.state.quit_to_menu();
+    world.game.state.set_run_state(next);
+
+    assert!(matches!(
+        world.game.state.run_state(),
+        RunState::MainMenu { .. }
+    ));
+    assert!(!world.logged("You were killed by Orc."));
+    let player = world.player();
+    assert!(world.hp(player) > 0);
+}
+
+#[test]
+fn kills_are_credited_to_the_attacker() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(30, 2, 5));
+    let goblin = world.spawn_monster("Goblin", 2, 1, stats(6, 1, 0));
+
+    for _ in 0..2 {
+        world.game.step(Command::Move {
+            delta_x: 1,
+            delta_y: 0,
+        });
+    }
+    assert!(!world.is_alive(goblin));
+    assert!(world.logged("You killed Goblin."));
+
+    let record = world.combat_record(player);
+    assert_eq!((record.damage_dealt, record.kills), (8, 1));
+}
+
+#[test]
+fn saved_games_load_back_as_they_were() {
+    let mut world = TestWorld::from_ascii(
+        "
+        ##########
+        #@.......#
+        #........#
+        ####+#####
+        #........#
+        ##########
+    ",
+    );
+    let orc = world.spawn_monster("Orc", 8, 2, stats(12, 1, 4));
+    world.insert(
+        orc,
+        Resistances {
+            kinds: vec![DamageType::Slashing],
+        },
+    );
+    world.insert(
+        orc,
+        Vulnerabilities {
+            kinds: vec![DamageType::Fire],
+        },
+    );
+    world.equip(orc, EquipmentSlot::Melee);
+    let player = world.player();
+    world.insert(
+        orc,
+        Poisoned {
+            turns: 9,
+            damage: 1,
+            source: Some(player),
+        },
+    );
+    world.spawn_named("Bear Trap", 5, 1);
+    world.game.advance(2);
+
+    let player_at = world.position(world.player());
+    let orc_at = world.position(orc);
+    let orc_hp = world.hp(orc);
+    world.save();
+    world.game.step(Command::Move {
+        delta_x: 0,
+        delta_y: 1,
+    });
+
+    world.load();
+
+    assert_eq!(world.position(world.player()), player_at);
+    assert_eq!(world.monster_names(), vec!["Orc"]);
+    assert_eq!(world.names_at(orc_at.0, orc_at.1), vec!["Orc"]);
+    assert_eq!(world.names_at(5, 1), vec!["Bear Trap"]);
+    assert!(!world.is_door_open(4, 3));
+    let orc = world.monster_named("Orc");
+    assert_eq!(world.count::<Poisoned>(), 1);
+    assert_eq!(world.hp(orc), orc_hp);
+
+    // And carries on from there
+    world.game.advance(1);
+    assert_ne!(world.position(orc), orc_at);
+}
+
+#[test]
+fn corpses_and_death_effects_survive_a_save() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    world.spawn_monster("Goblin", 2, 1, stats(1, 0, 0));
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    let beetle = world.spawn_monster("Bloated Beetle", 2, 2, stats(1, 0, 0));
+    world.insert(
+        beetle,
+        OnDeath {
+            effects: vec![DeathEffect::Explode {
+                radius: 1,
+                damage: 8,
+                kind: DamageType::Fire,
+            }],
+        },
+    );
+    world.save();
+
+    world.load();
+    assert_eq!(world.count::<Corpse>(), 1);
+    assert!(world.names_at(2, 1).contains(&"Goblin corpse".to_string()));
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 1,
+    });
+
+    let player = world.player();
+    assert_eq!(world.hp(player), 30 - 8);
+    assert_eq!(world.count::<Corpse>(), 2);
+}
+
+#[test]
+fn saves_from_another_version_are_deleted_and_the_world_kept() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let save_file = world.game.state.options.save_file.clone();
+    std::fs::write(&save_file, "1 {}").unwrap();
+
+    let error = world.game.state.continue_game().unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(!world.has_save());
+    assert_eq!(world.position(world.player()), (1, 1));
+}
+
+#[test]
+fn saves_that_cannot_be_read_are_kept_for_next_time() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let save_file = world.game.state.options.save_file.clone();
+    std::fs::create_dir(&save_file).unwrap();
+
+    assert!(world.game.state.continue_game().is_err());
+
+    assert!(world.has_save());
+    std::fs::remove_dir(&save_file).unwrap();
+}
+
+#[test]
+fn walls_block_sight() {
+    let mut world = TestWorld::from_ascii(
+        "
+        #########
+        #@..#...#
+        #...#...#
+        #########
+        ",
+    );
+
+    world.game.advance(1);
+
+    assert!(world.is_visible(3, 2));
+    assert!(world.is_visible(4, 1));
+    assert!(!world.is_visible(6, 1));
+}
+
+#[test]
+fn doors_block_sight_until_bumped_open() {
+    let mut world = TestWorld::from_ascii(
+        "
+        #########
+        #..@+...#
+        #########
+        ",
+    );
+    world.game.advance(1);
+    assert!(!world.is_visible(6, 1));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    assert!(world.is_door_open(4, 1));
+    assert_eq!(world.position(world.player()), (3, 1));
+    assert!(world.is_visible(6, 1));
+
+    world.game.step(Command::CloseDoor);
+    assert!(!world.is_door_open(4, 1));
+    assert!(!world.is_visible(6, 1));
+}
+
+#[test]
+fn monsters_open_doors_on_their_path() {
+    let mut world = TestWorld::from_ascii(
+        "
+        #########
+        #@......#
+        #...+...#
+        #########
+        ",
+    );
+    // Stands in the way without blocking the orc's view of the player
+    let bat = world.spawn_monster("Bat", 4, 1, stats(6, 0, 0));
+    world.insert(
+        bat,
+        Initiative {
+            speed: 0,
+            energy: 0,
+        },
+    );
+    let orc = world.spawn_monster("Orc", 7, 1, stats(10, 0, 0));
+
+    world.game.advance(6);
+
+    assert!(world.is_door_open(4, 2));
+    let (x, _) = world.position(orc);
+    assert!(x < 4, "orc stopped at x {}", x);
+}
+
+#[test]
+fn traps_go_off_under_whoever_moves_onto_them() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let trap = world.spawn_named("Bear Trap", 2, 1);
+    let other_trap = world.spawn_named("Bear Trap", 5, 1);
+    let orc = world.spawn_monster("Orc", 7, 1, stats(10, 0, 0));
+    assert!(world.is_hidden(trap));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    assert_eq!(world.hp(world.player()), 24);
+    assert!(world.logged("You set off the Bear Trap!"));
+    assert!(!world.is_hidden(trap));
+
+    world.game.advance(3);
+    assert_eq!(world.hp(orc), 4);
+    assert!(!world.is_hidden(other_trap));
+    assert_eq!(world.count::<CombatRecord>(), 0);
+}
+
+#[test]
+fn alarms_bring_monsters_that_cannot_see_the_player() {
+    let mut world = TestWorld::from_ascii(
+        "
+        ###########
+        #@..#.....#
+        #...#.....#
+        #.........#
+        ###########
+        ",
+    );
+    world.spawn_named("Alarm Trap", 2, 1);
+    let orc = world.spawn_monster("Orc", 9, 1, stats(10, 0, 0));
+
+    world.game.advance(3);
+    assert_eq!(world.position(orc), (9, 1));
+
+    world.game.step(Command::Move {
+        delta_x: 1,
+        delta_y: 0,
+    });
+    world.game.advance(3);
+
+    assert!(world.logged("An alarm rings out!"));
+    let (x, _) = world.position(orc);
+    assert!(x < 9, "orc stayed at x {}", x);
+}
+
+#[test]
+fn the_player_spots_hidden_traps_in_view() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let trap = world.spawn_named("Bear Trap", 5, 2);
+
+    world.game.advance(30);
+
+    assert!(!world.is_hidden(trap));
+    assert!(world.logged("You spot a Bear Trap."));
+    assert_eq!(world.hp(world.player()), 30);
+}
+
+#[test]
+fn monster_ignores_a_player_it_cannot_see() {
+    let mut world = TestWorld::from_ascii(
+        "
+        ##########
+        #@..######
+        #...#....#
+        ##########
+        ",
+    );
+    let orc = world.spawn_monster("Orc", 7, 2, stats(10, 0, 5));
+
+    world.game.advance(5);
+
+    assert_eq!(world.position(orc), (7, 2));
+    assert_eq!(world.hp(world.player()), 30);
+}
+
+#[test]
+fn fast_monster_acts_twice_per_turn() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(30, 0, 0));
+    let bat = world.spawn_monster("Bat", 2, 1, stats(6, 0, 1));
+    world.insert(
+        bat,
+        Initiative {
+            speed: 200,
+            energy: 0,
+        },
+    );
+
+    world.game.advance(1);
+    let hp = world.hp(player);
+    world.game.advance(2);
+
+    assert_eq!(world.hp(player), hp - 4);
+}
+
+#[test]
+fn slow_monster_skips_every_other_turn() {
+    let mut world = TestWorld::from_ascii(ROOM);
+    let player = world.player();
+    world.insert(player, stats(30, 0, 0));
+    let zombie = world.spawn_monster("Zombie", 2, 1, stats(24, 0, 1));
+    world.insert(
+        zombie,
+        Initiative {
+            speed: 50,
+            energy: 0,
+        },
+    );
+
+    world.game.advance(1);
+    let hp = world.hp(player);
+    world.game.advance(4);
+
+    assert_eq!(world.hp(player), hp - 2);
+}